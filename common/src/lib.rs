@@ -1,3 +1,9 @@
+pub mod mixed_radix;
+pub mod xorshift;
+
+pub use mixed_radix::MixedRadix;
+pub use xorshift::Xorshift;
+
 use std::fs::File;
 use std::io;
 use std::io::BufRead;
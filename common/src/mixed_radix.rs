@@ -0,0 +1,209 @@
+use rayon::prelude::*;
+
+/// Every combination of digits where the digit at position `i` runs from 0 to
+/// `radices[i] - 1`, counting like an odometer with the first position turning fastest.
+///
+/// Each combination has an index, so the states can be looked up directly, iterated from
+/// both ends or split across threads.
+#[derive(Debug, Clone)]
+pub struct MixedRadix {
+    radices: Vec<u32>,
+    len: usize,
+    gray: bool,
+}
+
+impl MixedRadix {
+    /// Panics if there are more combinations than fit into `usize`.
+    pub fn new(radices: Vec<u32>) -> Self {
+        let len = radices
+            .iter()
+            .try_fold(1usize, |len, &radix| len.checked_mul(radix as usize))
+            .expect("too many combinations");
+        Self {
+            radices,
+            len,
+            gray: false,
+        }
+    }
+
+    /// `positions` digits that all run from 0 to `radix - 1`.
+    pub fn uniform(positions: usize, radix: u32) -> Self {
+        Self::new(vec![radix; positions])
+    }
+
+    /// Orders the states as a reflected Gray code: consecutive states differ in a single
+    /// position, by one.
+    pub fn gray(mut self) -> Self {
+        self.gray = true;
+        self
+    }
+
+    pub fn radices(&self) -> &[u32] {
+        &self.radices
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The state at `index` in iteration order.
+    pub fn get(&self, index: usize) -> Option<Vec<u32>> {
+        (index < self.len).then(|| self.state(index))
+    }
+
+    fn state(&self, index: usize) -> Vec<u32> {
+        let mut rest = index;
+        self.radices
+            .iter()
+            .map(|&radix| {
+                let digit = (rest % radix as usize) as u32;
+                rest /= radix as usize;
+                // the digit runs backwards while the positions above it have an odd count
+                if self.gray && rest % 2 == 1 {
+                    radix - 1 - digit
+                } else {
+                    digit
+                }
+            })
+            .collect()
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            counter: self,
+            front: 0,
+            back: self.len,
+        }
+    }
+
+    pub fn par_iter(&self) -> impl IndexedParallelIterator<Item = Vec<u32>> + '_ {
+        (0..self.len)
+            .into_par_iter()
+            .map(move |index| self.state(index))
+    }
+}
+
+impl<'a> IntoIterator for &'a MixedRadix {
+    type Item = Vec<u32>;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub struct Iter<'a> {
+    counter: &'a MixedRadix,
+    front: usize,
+    back: usize,
+}
+
+impl Iterator for Iter<'_> {
+    type Item = Vec<u32>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.front += 1;
+        Some(self.counter.state(self.front - 1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.front = self.front.saturating_add(n).min(self.back);
+        self.next()
+    }
+}
+
+impl DoubleEndedIterator for Iter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(self.counter.state(self.back))
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_like_an_odometer() {
+        let counter = MixedRadix::new(vec![2, 3]);
+        let states: Vec<Vec<u32>> = counter.iter().collect();
+        assert_eq!(
+            states,
+            vec![
+                vec![0, 0],
+                vec![1, 0],
+                vec![0, 1],
+                vec![1, 1],
+                vec![0, 2],
+                vec![1, 2]
+            ]
+        );
+        assert_eq!(counter.len(), 6);
+        assert_eq!(counter.iter().len(), 6);
+        assert_eq!(counter.get(3), Some(vec![1, 1]));
+        assert_eq!(counter.get(6), None);
+        assert_eq!(counter.iter().next_back(), Some(vec![1, 2]));
+        assert_eq!(counter.iter().nth(4), Some(vec![0, 2]));
+    }
+
+    #[test]
+    fn test_edge_cases() {
+        assert_eq!(
+            MixedRadix::new(vec![]).iter().collect::<Vec<_>>(),
+            vec![Vec::<u32>::new()]
+        );
+        assert!(MixedRadix::new(vec![3, 0]).is_empty());
+        assert_eq!(MixedRadix::uniform(3, 1).len(), 1);
+    }
+
+    #[test]
+    fn test_gray_code() {
+        for radices in [vec![2, 2, 2], vec![3, 2, 4], vec![5, 3], vec![1, 4, 3]] {
+            let counter = MixedRadix::new(radices).gray();
+            let states: Vec<Vec<u32>> = counter.iter().collect();
+
+            for pair in states.windows(2) {
+                let changes: Vec<i64> = pair[0]
+                    .iter()
+                    .zip(&pair[1])
+                    .map(|(a, b)| *b as i64 - *a as i64)
+                    .filter(|change| *change != 0)
+                    .collect();
+                assert!(changes == [1] || changes == [-1], "{:?}", pair);
+            }
+
+            let mut sorted = states.clone();
+            sorted.sort();
+            sorted.dedup();
+            assert_eq!(sorted.len(), counter.len());
+        }
+
+        let binary: Vec<Vec<u32>> = MixedRadix::uniform(2, 2).gray().iter().collect();
+        assert_eq!(binary, vec![vec![0, 0], vec![1, 0], vec![1, 1], vec![0, 1]]);
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let counter = MixedRadix::new(vec![3, 4, 2, 5]).gray();
+        let parallel: Vec<Vec<u32>> = counter.par_iter().collect();
+        assert_eq!(parallel, counter.iter().collect::<Vec<_>>());
+        assert_eq!(counter.par_iter().filter(|state| state[0] == 0).count(), 40);
+    }
+}
@@ -0,0 +1,293 @@
+use crate::{Direction, Position};
+use std::collections::{HashMap, VecDeque};
+
+const ROOT: usize = 0;
+
+/// One occurrence of a dictionary word in the grid.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WordMatch {
+    pub word: usize,
+    pub start: Position,
+    pub direction: Direction,
+    pub len: usize,
+}
+
+impl WordMatch {
+    pub fn positions(&self) -> Vec<Position> {
+        (0..self.len as i32)
+            .map(|i| Position {
+                x: self.start.x + self.direction.x * i,
+                y: self.start.y + self.direction.y * i,
+            })
+            .collect()
+    }
+}
+
+pub struct SearchResult {
+    pub words: Vec<String>,
+    pub matches: Vec<WordMatch>,
+}
+
+impl SearchResult {
+    pub fn count(&self, word: &str) -> usize {
+        let word = word.to_ascii_uppercase();
+        self.matches
+            .iter()
+            .filter(|m| self.words[m.word] == word)
+            .count()
+    }
+
+    pub fn counts(&self) -> Vec<(&str, usize)> {
+        let mut counts = vec![0; self.words.len()];
+        for m in &self.matches {
+            counts[m.word] += 1;
+        }
+
+        self.words.iter().map(String::as_str).zip(counts).collect()
+    }
+}
+
+#[derive(Default)]
+struct Node {
+    next: HashMap<char, usize>,
+    fail: usize,
+    // words ending in this node, including the ones reachable over fail links
+    output: Vec<usize>,
+}
+
+/// Aho–Corasick automaton over a word list, matching case insensitive like `check`.
+pub struct Dictionary {
+    words: Vec<String>,
+    nodes: Vec<Node>,
+}
+
+impl Dictionary {
+    pub fn new<I, S>(words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut dictionary = Self {
+            words: Vec::new(),
+            nodes: vec![Node::default()],
+        };
+
+        for word in words {
+            let word = word.as_ref().trim().to_ascii_uppercase();
+            if word.is_empty() || dictionary.words.contains(&word) {
+                continue;
+            }
+            dictionary.insert(word);
+        }
+        dictionary.build_fail_links();
+
+        dictionary
+    }
+
+    fn insert(&mut self, word: String) {
+        let mut node = ROOT;
+        for letter in word.chars() {
+            node = match self.nodes[node].next.get(&letter) {
+                Some(next) => *next,
+                None => {
+                    self.nodes.push(Node::default());
+                    let next = self.nodes.len() - 1;
+                    self.nodes[node].next.insert(letter, next);
+                    next
+                }
+            };
+        }
+        self.nodes[node].output.push(self.words.len());
+        self.words.push(word);
+    }
+
+    fn build_fail_links(&mut self) {
+        let mut queue: VecDeque<usize> = self.nodes[ROOT].next.values().copied().collect();
+
+        while let Some(node) = queue.pop_front() {
+            let edges: Vec<(char, usize)> = self.nodes[node]
+                .next
+                .iter()
+                .map(|(letter, child)| (*letter, *child))
+                .collect();
+
+            for (letter, child) in edges {
+                let mut fail = self.nodes[node].fail;
+                while fail != ROOT && !self.nodes[fail].next.contains_key(&letter) {
+                    fail = self.nodes[fail].fail;
+                }
+                let fail = self.nodes[fail].next.get(&letter).copied().unwrap_or(ROOT);
+
+                self.nodes[child].fail = fail;
+                let inherited = self.nodes[fail].output.clone();
+                self.nodes[child].output.extend(inherited);
+                queue.push_back(child);
+            }
+        }
+    }
+
+    fn step(&self, mut node: usize, letter: char) -> usize {
+        loop {
+            if let Some(next) = self.nodes[node].next.get(&letter) {
+                return *next;
+            }
+            if node == ROOT {
+                return ROOT;
+            }
+            node = self.nodes[node].fail;
+        }
+    }
+
+    /// Scans every row, column and both diagonal families once in each direction.
+    pub fn search(&self, state: &[Vec<char>]) -> SearchResult {
+        let mut matches = Vec::new();
+
+        for (line, direction) in grid_lines(state) {
+            self.scan(state, &line, &direction, &mut matches);
+
+            let reversed: Vec<Position> = line.into_iter().rev().collect();
+            let direction = Direction {
+                x: -direction.x,
+                y: -direction.y,
+            };
+            self.scan(state, &reversed, &direction, &mut matches);
+        }
+
+        SearchResult {
+            words: self.words.clone(),
+            matches,
+        }
+    }
+
+    fn scan(
+        &self,
+        state: &[Vec<char>],
+        line: &[Position],
+        direction: &Direction,
+        matches: &mut Vec<WordMatch>,
+    ) {
+        let mut node = ROOT;
+        for (index, position) in line.iter().enumerate() {
+            let letter = state
+                .get(position.y as usize)
+                .and_then(|row| row.get(position.x as usize));
+            let Some(letter) = letter else {
+                // ragged rows break the line like a letter outside the alphabet
+                node = ROOT;
+                continue;
+            };
+
+            node = self.step(node, letter.to_ascii_uppercase());
+            for word in &self.nodes[node].output {
+                let len = self.words[*word].chars().count();
+                matches.push(WordMatch {
+                    word: *word,
+                    start: line[index + 1 - len].clone(),
+                    direction: direction.clone(),
+                    len,
+                });
+            }
+        }
+    }
+}
+
+fn grid_lines(state: &[Vec<char>]) -> Vec<(Vec<Position>, Direction)> {
+    let height = state.len() as i32;
+    let width = state.iter().map(Vec::len).max().unwrap_or(0) as i32;
+    let walk = |mut x: i32, mut y: i32, dx: i32, dy: i32| {
+        let mut line = Vec::new();
+        while x >= 0 && x < width && y >= 0 && y < height {
+            line.push(Position { x, y });
+            x += dx;
+            y += dy;
+        }
+        (line, Direction { x: dx, y: dy })
+    };
+
+    let mut lines = Vec::new();
+    for y in 0..height {
+        lines.push(walk(0, y, 1, 0));
+    }
+    for x in 0..width {
+        lines.push(walk(x, 0, 0, 1));
+    }
+    for x in 0..width {
+        lines.push(walk(x, 0, 1, 1));
+        lines.push(walk(x, 0, -1, 1));
+    }
+    for y in 1..height {
+        lines.push(walk(0, y, 1, 1));
+        lines.push(walk(width - 1, y, -1, 1));
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::check;
+
+    fn example() -> Vec<Vec<char>> {
+        crate::create_state("test.txt")
+    }
+
+    #[test]
+    fn test_xmas_count() {
+        let result = Dictionary::new(["XMAS"]).search(&example());
+        assert_eq!(result.count("XMAS"), 18);
+    }
+
+    #[test]
+    fn test_overlapping_words() {
+        let state = vec!["ABCD".chars().collect::<Vec<_>>()];
+        let result = Dictionary::new(["abcd", "bc", "c", "dc"]).search(&state);
+
+        assert_eq!(result.count("ABCD"), 1);
+        assert_eq!(result.count("BC"), 1);
+        assert_eq!(result.count("DC"), 1);
+        // a single letter lies on four lines, each scanned both ways
+        assert_eq!(result.count("C"), 8);
+
+        let dc = result
+            .matches
+            .iter()
+            .find(|m| result.words[m.word] == "DC")
+            .unwrap();
+        assert_eq!(
+            dc.positions(),
+            vec![Position { x: 3, y: 0 }, Position { x: 2, y: 0 }]
+        );
+    }
+
+    #[test]
+    fn test_matches_check() {
+        let state = example();
+        let words = ["XMAS", "MAS", "SAM", "AMX", "MM"];
+        let result = Dictionary::new(words).search(&state);
+
+        let directions = [
+            (1, 0),
+            (-1, 0),
+            (0, 1),
+            (0, -1),
+            (1, 1),
+            (-1, -1),
+            (-1, 1),
+            (1, -1),
+        ];
+        for word in words {
+            let mut expected = 0;
+            for y in 0..state.len() as i32 {
+                for x in 0..state[0].len() as i32 {
+                    for (dx, dy) in directions {
+                        let position = Position { x, y };
+                        let direction = Direction { x: dx, y: dy };
+                        expected += check(&state, &position, &direction, word);
+                    }
+                }
+            }
+            assert_eq!(result.count(word), expected, "{word}");
+        }
+    }
+}
@@ -1,9 +1,55 @@
+mod dictionary;
+mod render;
+
 use common::read_lines;
+use dictionary::Dictionary;
+use render::{Highlight, Renderer};
 use std::io::BufRead;
 use std::ops::{AddAssign, Sub};
 
 fn main() {
     let state = create_state("door_04/input.txt");
+
+    // door_04 [--ansi | --html] [--match=N] [WORD ...]
+    let mut words = Vec::new();
+    let mut output = None;
+    let mut highlight = Highlight::All;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--ansi" | "--html" => output = Some(arg),
+            _ if arg.starts_with("--match=") => {
+                let index = arg["--match=".len()..].parse().expect("match index");
+                highlight = Highlight::Single(index);
+            }
+            _ => words.push(arg),
+        }
+    }
+
+    if !words.is_empty() {
+        let result = Dictionary::new(&words).search(&state);
+        if let Highlight::Single(index) = highlight {
+            if index >= result.matches.len() {
+                return eprintln!(
+                    "--match={index} is out of range, the search found {} matches",
+                    result.matches.len()
+                );
+            }
+        }
+        let renderer = Renderer::new(&state, &result);
+        match output.as_deref() {
+            Some("--ansi") => print!("{}", renderer.ansi(&highlight)),
+            Some("--html") => print!("{}", renderer.html(&highlight)),
+            _ => {
+                for (word, count) in result.counts() {
+                    println!("{word}: {count}");
+                }
+            }
+        }
+        return;
+    }
+
+    let result = Dictionary::new(["XMAS"]).search(&state);
+    println!("{}", result.count("XMAS"));
     let count = count_xmases(&state);
     println!("{}", count);
 }
@@ -14,11 +60,13 @@ struct Letter {
     pub y: i32,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct Position {
     pub x: i32,
     pub y: i32,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct Direction {
     pub x: i32,
     pub y: i32,
@@ -123,7 +171,7 @@ mod tests {
         let direction = Direction { x: 1, y: 0 };
 
         let p = Position { x: 0, y: 0 };
-        let count = check(&s, &p, &direction);
+        let count = check(&s, &p, &direction, "XMAS");
 
         assert_eq!(count, 1);
     }
@@ -0,0 +1,181 @@
+use crate::dictionary::{SearchResult, WordMatch};
+use crate::Position;
+use std::collections::HashMap;
+
+const ANSI_RESET: &str = "\x1b[0m";
+const ANSI_DIM: &str = "\x1b[2m";
+const ANSI_COLOURS: [&str; 6] = [
+    "\x1b[1;31m",
+    "\x1b[1;32m",
+    "\x1b[1;33m",
+    "\x1b[1;34m",
+    "\x1b[1;35m",
+    "\x1b[1;36m",
+];
+const HTML_COLOURS: [&str; 6] = [
+    "#e06c75", "#98c379", "#e5c07b", "#61afef", "#c678dd", "#56b6c2",
+];
+
+pub enum Highlight {
+    All,
+    Single(usize),
+}
+
+/// Highlights the matches of a search in the grid they were found in.
+pub struct Renderer<'a> {
+    state: &'a [Vec<char>],
+    result: &'a SearchResult,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(state: &'a [Vec<char>], result: &'a SearchResult) -> Self {
+        Self { state, result }
+    }
+
+    fn selected(&self, highlight: &Highlight) -> Vec<&'a WordMatch> {
+        match highlight {
+            Highlight::All => self.result.matches.iter().collect(),
+            Highlight::Single(index) => self.result.matches.get(*index).into_iter().collect(),
+        }
+    }
+
+    // Maps each highlighted cell to the word it belongs to; later matches win on overlaps.
+    fn cells(&self, highlight: &Highlight) -> HashMap<Position, usize> {
+        let mut cells = HashMap::new();
+        for m in self.selected(highlight) {
+            for position in m.positions() {
+                cells.insert(position, m.word);
+            }
+        }
+        cells
+    }
+
+    pub fn ansi(&self, highlight: &Highlight) -> String {
+        let cells = self.cells(highlight);
+        let mut output = String::new();
+
+        for (y, line) in self.state.iter().enumerate() {
+            for (x, letter) in line.iter().enumerate() {
+                let position = Position {
+                    x: x as i32,
+                    y: y as i32,
+                };
+                let style = match cells.get(&position) {
+                    Some(word) => ANSI_COLOURS[word % ANSI_COLOURS.len()],
+                    None => ANSI_DIM,
+                };
+                output.push_str(style);
+                output.push(*letter);
+                output.push_str(ANSI_RESET);
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+
+    pub fn html(&self, highlight: &Highlight) -> String {
+        let cells = self.cells(highlight);
+        let mut output = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Word search</title>\n\
+             <style>\nbody { background: #282c34; color: #5c6370; }\n\
+             pre { font-size: 16px; line-height: 1.2; }\n\
+             span { font-weight: bold; }\n</style>\n</head>\n<body>\n<pre>\n",
+        );
+
+        for (y, line) in self.state.iter().enumerate() {
+            for (x, letter) in line.iter().enumerate() {
+                let position = Position {
+                    x: x as i32,
+                    y: y as i32,
+                };
+                let letter = escape(&letter.to_string());
+                match cells.get(&position) {
+                    Some(word) => output.push_str(&format!(
+                        "<span style=\"color: {}\" title=\"{}\">{letter}</span>",
+                        HTML_COLOURS[word % HTML_COLOURS.len()],
+                        escape(&self.result.words[*word])
+                    )),
+                    None => output.push_str(&letter),
+                }
+            }
+            output.push('\n');
+        }
+
+        output.push_str("</pre>\n</body>\n</html>\n");
+        output
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for letter in text.chars() {
+        match letter {
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(letter),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dictionary::Dictionary;
+
+    fn grid() -> Vec<Vec<char>> {
+        vec!["XMAS".chars().collect(), "QQQQ".chars().collect()]
+    }
+
+    #[test]
+    fn test_ansi_overlay() {
+        let state = grid();
+        let result = Dictionary::new(["XMAS"]).search(&state);
+        let output = Renderer::new(&state, &result).ansi(&Highlight::All);
+
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(
+            lines[0],
+            "\x1b[1;31mX\x1b[0m\x1b[1;31mM\x1b[0m\x1b[1;31mA\x1b[0m\x1b[1;31mS\x1b[0m"
+        );
+        assert_eq!(lines[1], "\x1b[2mQ\x1b[0m".repeat(4));
+    }
+
+    #[test]
+    fn test_single_match() {
+        let state = grid();
+        let result = Dictionary::new(["XMAS", "AS"]).search(&state);
+        let index = result
+            .matches
+            .iter()
+            .position(|m| result.words[m.word] == "AS")
+            .unwrap();
+        let output = Renderer::new(&state, &result).ansi(&Highlight::Single(index));
+
+        assert!(output.starts_with("\x1b[2mX\x1b[0m\x1b[2mM\x1b[0m\x1b[1;32mA"));
+    }
+
+    #[test]
+    fn test_html() {
+        let state = grid();
+        let result = Dictionary::new(["XMAS"]).search(&state);
+        let output = Renderer::new(&state, &result).html(&Highlight::All);
+
+        assert!(output.starts_with("<!DOCTYPE html>"));
+        assert!(output.contains("<span style=\"color: #e06c75\" title=\"XMAS\">X</span>"));
+        assert!(output.contains("\nQQQQ\n"));
+    }
+
+    #[test]
+    fn test_html_escapes_words() {
+        let state = vec!["A\"<B".chars().collect()];
+        let result = Dictionary::new(["A\"<B"]).search(&state);
+        let output = Renderer::new(&state, &result).html(&Highlight::All);
+
+        assert!(output.contains("title=\"A&quot;&lt;B\">&quot;</span>"));
+        assert!(!output.contains("title=\"A\""));
+    }
+}
@@ -0,0 +1,175 @@
+use crate::{Pages, Rule};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+/// Why the rules do not form a strict weak order on a set of pages.
+#[derive(Debug, PartialEq)]
+pub enum OrderError {
+    /// A rule `X|X`.
+    Reflexive(i32),
+    /// Both `X|Y` and `Y|X`.
+    Asymmetry(i32, i32),
+    /// `X|Y` and `Y|Z` but not `X|Z`.
+    Intransitive(i32, i32, i32),
+    /// `X` and `Z` are ordered although both are unordered with `Y`.
+    Incomparability(i32, i32, i32),
+}
+
+impl Display for OrderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrderError::Reflexive(x) => write!(f, "rule {x}|{x} orders a page before itself"),
+            OrderError::Asymmetry(x, y) => write!(f, "rules {x}|{y} and {y}|{x} contradict"),
+            OrderError::Intransitive(x, y, z) => {
+                write!(f, "rules {x}|{y} and {y}|{z} exist but {x}|{z} does not")
+            }
+            OrderError::Incomparability(x, y, z) => {
+                write!(f, "{x} and {z} are ordered but neither is ordered with {y}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for OrderError {}
+
+/// Compares pages by the ordering rules, `Less` meaning the first page must be printed first.
+pub struct PageOrder<'a> {
+    rules: &'a HashMap<i32, Rule>,
+}
+
+impl<'a> PageOrder<'a> {
+    pub fn new(rules: &'a HashMap<i32, Rule>) -> Self {
+        Self { rules }
+    }
+
+    fn before(&self, x: i32, y: i32) -> bool {
+        self.rules
+            .get(&x)
+            .map(|rule| rule.before.contains(&y))
+            .unwrap_or(false)
+    }
+
+    pub fn compare(&self, x: &i32, y: &i32) -> Ordering {
+        if self.before(*x, *y) {
+            Ordering::Less
+        } else if self.before(*y, *x) {
+            Ordering::Greater
+        } else {
+            Ordering::Equal
+        }
+    }
+
+    /// Checks that the comparator is a strict weak order on the given pages, which is what
+    /// `sort_by` and `is_sorted_by` rely on.
+    pub fn validate(&self, pages: &[i32]) -> Result<(), OrderError> {
+        for x in pages {
+            if self.before(*x, *x) {
+                return Err(OrderError::Reflexive(*x));
+            }
+        }
+
+        for x in pages {
+            for y in pages.iter().filter(|y| *y != x) {
+                if self.before(*x, *y) && self.before(*y, *x) {
+                    return Err(OrderError::Asymmetry(*x, *y));
+                }
+            }
+        }
+
+        for x in pages {
+            for y in pages {
+                for z in pages {
+                    if x == y || y == z || x == z {
+                        continue;
+                    }
+                    let xy = self.compare(x, y);
+                    let yz = self.compare(y, z);
+                    let xz = self.compare(x, z);
+                    if xy == Ordering::Less && yz == Ordering::Less && xz != Ordering::Less {
+                        return Err(OrderError::Intransitive(*x, *y, *z));
+                    }
+                    if xy == Ordering::Equal && yz == Ordering::Equal && xz != Ordering::Equal {
+                        return Err(OrderError::Incomparability(*x, *y, *z));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Pages {
+    pub fn is_sorted_by_order(&self, order: &PageOrder) -> bool {
+        self.0
+            .is_sorted_by(|x, y| order.compare(x, y) != Ordering::Greater)
+    }
+
+    pub fn sort_by_order(&mut self, order: &PageOrder) {
+        self.0.sort_by(|x, y| order.compare(x, y));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read_file;
+    use crate::tests::rules;
+
+    #[test]
+    fn test_compare() {
+        let rules = rules(&[(1, 2)]);
+        let order = PageOrder::new(&rules);
+        assert_eq!(order.compare(&1, &2), Ordering::Less);
+        assert_eq!(order.compare(&2, &1), Ordering::Greater);
+        assert_eq!(order.compare(&1, &3), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_validate() {
+        let valid = rules(&[(1, 2), (2, 3), (1, 3)]);
+        assert_eq!(PageOrder::new(&valid).validate(&[3, 1, 2]), Ok(()));
+
+        let reflexive = rules(&[(1, 1)]);
+        assert_eq!(
+            PageOrder::new(&reflexive).validate(&[1]),
+            Err(OrderError::Reflexive(1))
+        );
+
+        let asymmetric = rules(&[(1, 2), (2, 1)]);
+        assert_eq!(
+            PageOrder::new(&asymmetric).validate(&[1, 2]),
+            Err(OrderError::Asymmetry(1, 2))
+        );
+
+        let intransitive = rules(&[(1, 2), (2, 3)]);
+        assert_eq!(
+            PageOrder::new(&intransitive).validate(&[1, 2, 3]),
+            Err(OrderError::Intransitive(1, 2, 3))
+        );
+        // the missing rule does not matter when its pages are not both in the update
+        assert_eq!(PageOrder::new(&intransitive).validate(&[1, 2]), Ok(()));
+
+        let partial = rules(&[(1, 2)]);
+        assert_eq!(
+            PageOrder::new(&partial).validate(&[1, 3, 2]),
+            Err(OrderError::Incomparability(1, 3, 2))
+        );
+    }
+
+    #[test]
+    fn test_sort_matches_ordered() {
+        let (rules, updates) = read_file("input.txt");
+        let order = PageOrder::new(&rules);
+
+        for update in updates {
+            order.validate(&update.0).unwrap();
+            assert_eq!(update.is_sorted_by_order(&order), update.is_correct(&rules));
+
+            let mut sorted = Pages(update.0.clone());
+            sorted.sort_by_order(&order);
+            assert_eq!(sorted.0, update.ordered(&rules).unwrap().0);
+        }
+    }
+}
@@ -0,0 +1,230 @@
+use crate::{Pages, Rule};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// The ordering rules as a directed graph with an edge `X -> Y` for every rule `X|Y`.
+pub struct RuleGraph {
+    edges: BTreeMap<i32, BTreeSet<i32>>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mark {
+    Unvisited,
+    InProgress,
+    Done,
+}
+
+struct Tarjan<'a> {
+    graph: &'a RuleGraph,
+    index: HashMap<i32, usize>,
+    low_link: HashMap<i32, usize>,
+    stack: Vec<i32>,
+    on_stack: BTreeSet<i32>,
+    components: Vec<Vec<i32>>,
+}
+
+impl RuleGraph {
+    pub fn new(rules: &HashMap<i32, Rule>) -> Self {
+        let mut edges: BTreeMap<i32, BTreeSet<i32>> = BTreeMap::new();
+        for rule in rules.values() {
+            for after in &rule.before {
+                edges.entry(rule.page).or_default().insert(*after);
+                edges.entry(*after).or_default();
+            }
+        }
+
+        Self { edges }
+    }
+
+    pub fn pages(&self) -> impl Iterator<Item = &i32> {
+        self.edges.keys()
+    }
+
+    fn successors(&self, page: i32) -> impl Iterator<Item = &i32> {
+        self.edges.get(&page).into_iter().flatten()
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph rules {\n");
+        for (page, successors) in &self.edges {
+            if successors.is_empty() {
+                dot.push_str(&format!("    {page};\n"));
+            }
+            for after in successors {
+                dot.push_str(&format!("    {page} -> {after};\n"));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Returns the pages of one cycle in rule order, if the rules contain any.
+    pub fn find_cycle(&self) -> Option<Vec<i32>> {
+        let mut marks: HashMap<i32, Mark> = self.pages().map(|p| (*p, Mark::Unvisited)).collect();
+        let mut path = Vec::new();
+
+        for page in self.pages() {
+            if marks[page] == Mark::Unvisited {
+                if let Some(cycle) = self.visit(*page, &mut marks, &mut path) {
+                    return Some(cycle);
+                }
+            }
+        }
+
+        None
+    }
+
+    fn visit(
+        &self,
+        page: i32,
+        marks: &mut HashMap<i32, Mark>,
+        path: &mut Vec<i32>,
+    ) -> Option<Vec<i32>> {
+        marks.insert(page, Mark::InProgress);
+        path.push(page);
+
+        for next in self.successors(page) {
+            match marks[next] {
+                Mark::InProgress => {
+                    let start = path.iter().position(|p| p == next).unwrap();
+                    return Some(path[start..].to_vec());
+                }
+                Mark::Unvisited => {
+                    if let Some(cycle) = self.visit(*next, marks, path) {
+                        return Some(cycle);
+                    }
+                }
+                Mark::Done => {}
+            }
+        }
+
+        path.pop();
+        marks.insert(page, Mark::Done);
+        None
+    }
+
+    /// Tarjan's algorithm; every component is sorted and components are ordered by their
+    /// smallest page.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<i32>> {
+        let mut tarjan = Tarjan {
+            graph: self,
+            index: HashMap::new(),
+            low_link: HashMap::new(),
+            stack: Vec::new(),
+            on_stack: BTreeSet::new(),
+            components: Vec::new(),
+        };
+
+        for page in self.pages() {
+            if !tarjan.index.contains_key(page) {
+                tarjan.connect(*page);
+            }
+        }
+
+        let mut components = tarjan.components;
+        components.iter_mut().for_each(|c| c.sort());
+        components.sort();
+        components
+    }
+
+    /// Pages that appear in an update but in no rule at all.
+    pub fn pages_without_rules(&self, updates: &[Pages]) -> BTreeSet<i32> {
+        updates
+            .iter()
+            .flat_map(|update| update.0.iter())
+            .filter(|page| !self.edges.contains_key(page))
+            .copied()
+            .collect()
+    }
+}
+
+impl Tarjan<'_> {
+    fn connect(&mut self, page: i32) {
+        let index = self.index.len();
+        self.index.insert(page, index);
+        self.low_link.insert(page, index);
+        self.stack.push(page);
+        self.on_stack.insert(page);
+
+        for next in self.graph.successors(page) {
+            if !self.index.contains_key(next) {
+                self.connect(*next);
+                let low = self.low_link[&page].min(self.low_link[next]);
+                self.low_link.insert(page, low);
+            } else if self.on_stack.contains(next) {
+                let low = self.low_link[&page].min(self.index[next]);
+                self.low_link.insert(page, low);
+            }
+        }
+
+        if self.low_link[&page] == self.index[&page] {
+            let mut component = Vec::new();
+            while let Some(member) = self.stack.pop() {
+                self.on_stack.remove(&member);
+                component.push(member);
+                if member == page {
+                    break;
+                }
+            }
+            self.components.push(component);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read_file;
+    use crate::tests::rules;
+
+    fn graph(pairs: &[(i32, i32)]) -> RuleGraph {
+        RuleGraph::new(&rules(pairs))
+    }
+
+    #[test]
+    fn test_dot() {
+        let graph = graph(&[(1, 2), (1, 3)]);
+        assert_eq!(
+            graph.to_dot(),
+            "digraph rules {\n    1 -> 2;\n    1 -> 3;\n    2;\n    3;\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_acyclic() {
+        let graph = graph(&[(1, 2), (2, 3), (1, 3)]);
+        assert_eq!(graph.find_cycle(), None);
+        assert_eq!(
+            graph.strongly_connected_components(),
+            vec![vec![1], vec![2], vec![3]]
+        );
+    }
+
+    #[test]
+    fn test_cycles() {
+        let graph = graph(&[(1, 2), (2, 3), (3, 1), (3, 4), (4, 5), (5, 4)]);
+        assert_eq!(graph.find_cycle(), Some(vec![1, 2, 3]));
+        assert_eq!(
+            graph.strongly_connected_components(),
+            vec![vec![1, 2, 3], vec![4, 5]]
+        );
+    }
+
+    #[test]
+    fn test_pages_without_rules() {
+        let graph = graph(&[(1, 2)]);
+        let updates = vec![Pages(vec![1, 7, 2]), Pages(vec![8])];
+        assert_eq!(graph.pages_without_rules(&updates), BTreeSet::from([7, 8]));
+    }
+
+    #[test]
+    fn test_input_rules_are_cyclic() {
+        let (rules, _) = read_file("input.txt");
+        let graph = RuleGraph::new(&rules);
+
+        let cycle = graph.find_cycle().unwrap();
+        for (page, next) in cycle.iter().zip(cycle.iter().cycle().skip(1)) {
+            assert!(rules[page].before.contains(next));
+        }
+        assert_eq!(graph.strongly_connected_components().len(), 1);
+    }
+}
@@ -1,19 +1,58 @@
+mod comparator;
+mod graph;
+mod ordering;
+mod rule_book;
+mod violations;
+
 use common::read_lines;
+use comparator::PageOrder;
+use graph::RuleGraph;
+use rule_book::RuleBook;
 use std::collections::{HashMap, HashSet};
 
 fn main() {
     let (rules, pages) = read_file("door_05/input.txt");
 
+    match std::env::args().nth(1).as_deref() {
+        Some("--explain") => {
+            explain(&rules, &pages);
+            return;
+        }
+        Some("--dot") => {
+            print!("{}", RuleGraph::new(&rules).to_dot());
+            return;
+        }
+        Some("--analyze") => {
+            analyze(&rules, &pages);
+            return;
+        }
+        Some("--interactive") => {
+            let mut book = RuleBook::new(rules, pages);
+            rule_book::run(&mut book, std::io::stdin().lock(), std::io::stdout())
+                .expect("unable to read commands");
+            return;
+        }
+        _ => {}
+    }
+
+    let order = PageOrder::new(&rules);
     let mut count = 0;
     let mut count_fixed = 0;
     for mut page in pages {
-        if page.is_correct(&rules) {
+        if order.validate(&page.0).is_ok() {
+            if page.is_sorted_by_order(&order) {
+                count += page.get_middle();
+            } else {
+                page.sort_by_order(&order);
+                count_fixed += page.get_middle();
+            }
+        } else if page.is_correct(&rules) {
             count += page.get_middle();
         } else {
-            while !page.is_correct(&rules) {
-                page.fix(&rules);
-            }
-
+            // the rules are no strict weak order on this update, but may still be acyclic
+            let page = page
+                .ordered(&rules)
+                .expect("rules of an update must not be cyclic");
             count_fixed += page.get_middle();
         }
     }
@@ -22,6 +61,45 @@ fn main() {
     println!("{}", count_fixed);
 }
 
+fn explain(rules: &HashMap<i32, Rule>, pages: &[Pages]) {
+    for (index, page) in pages.iter().enumerate() {
+        let violations = page.violations(rules);
+        if violations.is_empty() {
+            continue;
+        }
+
+        println!("update {} {:?}", index + 1, page.0);
+        for violation in violations {
+            println!("  {}", violation);
+        }
+    }
+
+    println!("most violated rules:");
+    for ((before, after), count) in violations::violation_summary(pages, rules).iter().take(10) {
+        println!("  {}|{}: {}", before, after, count);
+    }
+}
+
+fn analyze(rules: &HashMap<i32, Rule>, pages: &[Pages]) {
+    let graph = RuleGraph::new(rules);
+
+    match graph.find_cycle() {
+        Some(cycle) => println!("cycle: {:?}", cycle),
+        None => println!("rules are acyclic"),
+    }
+
+    let components = graph.strongly_connected_components();
+    println!("strongly connected components: {}", components.len());
+    for component in components.iter().filter(|c| c.len() > 1) {
+        println!("  {} pages: {:?}", component.len(), component);
+    }
+
+    println!(
+        "pages without rules: {:?}",
+        graph.pages_without_rules(pages)
+    );
+}
+
 struct Rule {
     pub page: i32,
     pub before: HashSet<i32>,
@@ -40,9 +118,12 @@ impl Rule {
     }
 }
 
+#[derive(Debug)]
 struct Pages(Vec<i32>);
 
 impl Pages {
+    // The swap-until-sorted repair `ordered` replaced; the tests still check against it.
+    #[cfg(test)]
     pub(crate) fn fix(&mut self, rules: &HashMap<i32, Rule>) {
         let mut first = 0;
         let mut second = 0;
@@ -138,6 +219,17 @@ fn read_file(path: &str) -> (HashMap<i32, Rule>, Vec<Pages>) {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
+    /// Rules from `X|Y` pairs, shared by the tests of all modules.
+    pub(crate) fn rules(pairs: &[(i32, i32)]) -> HashMap<i32, Rule> {
+        let mut rules: HashMap<i32, Rule> = HashMap::new();
+        for (x, y) in pairs {
+            rules.entry(*x).or_insert(Rule::new(*x)).add(*y);
+        }
+        rules
+    }
+
     #[test]
     fn test_page() {}
 }
@@ -0,0 +1,114 @@
+use crate::{Pages, Rule};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, PartialEq)]
+pub struct CycleError {
+    /// Pages of the update that could not be placed because their rules form a cycle.
+    pub pages: Vec<i32>,
+}
+
+impl Display for CycleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ordering rules contain a cycle between pages {:?}",
+            self.pages
+        )
+    }
+}
+
+impl std::error::Error for CycleError {}
+
+impl Pages {
+    /// Orders the update by a topological sort of the rules restricted to its pages.
+    /// Pages without a constraint between them keep their relative order.
+    pub fn ordered(&self, rules: &HashMap<i32, Rule>) -> Result<Pages, CycleError> {
+        let len = self.0.len();
+        let mut successors: Vec<Vec<usize>> = vec![Vec::new(); len];
+        let mut in_degree = vec![0; len];
+
+        for (from, page) in self.0.iter().enumerate() {
+            let Some(rule) = rules.get(page) else {
+                continue;
+            };
+            for (to, other) in self.0.iter().enumerate() {
+                if from != to && rule.before.contains(other) {
+                    successors[from].push(to);
+                    in_degree[to] += 1;
+                }
+            }
+        }
+
+        let mut ready: BinaryHeap<Reverse<usize>> = (0..len)
+            .filter(|index| in_degree[*index] == 0)
+            .map(Reverse)
+            .collect();
+        let mut ordered = Pages::new();
+
+        while let Some(Reverse(index)) = ready.pop() {
+            ordered.add_page(self.0[index]);
+            for next in &successors[index] {
+                in_degree[*next] -= 1;
+                if in_degree[*next] == 0 {
+                    ready.push(Reverse(*next));
+                }
+            }
+        }
+
+        if ordered.0.len() < len {
+            let pages = (0..len)
+                .filter(|index| in_degree[*index] > 0)
+                .map(|index| self.0[index])
+                .collect();
+            return Err(CycleError { pages });
+        }
+
+        Ok(ordered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read_file;
+    use crate::tests::rules;
+
+    fn pages(values: &[i32]) -> Pages {
+        let mut pages = Pages::new();
+        values.iter().for_each(|x| pages.add_page(*x));
+        pages
+    }
+
+    #[test]
+    fn test_ordered() {
+        let rules = rules(&[(97, 13), (97, 47), (75, 47), (75, 97), (47, 13), (61, 13)]);
+        let ordered = pages(&[61, 13, 47, 75, 97]).ordered(&rules).unwrap();
+        assert_eq!(ordered.0, vec![61, 75, 97, 47, 13]);
+        assert!(ordered.is_correct(&rules));
+    }
+
+    #[test]
+    fn test_cycle() {
+        let rules = rules(&[(1, 2), (2, 3), (3, 1), (4, 1)]);
+        let error = pages(&[4, 3, 2, 1, 5]).ordered(&rules).unwrap_err();
+        assert_eq!(error.pages, vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn test_ordered_matches_fix() {
+        let (rules, updates) = read_file("input.txt");
+
+        for update in updates {
+            let ordered = update.ordered(&rules).unwrap();
+            assert!(ordered.is_correct(&rules));
+
+            let mut fixed = Pages(update.0.clone());
+            while !fixed.is_correct(&rules) {
+                fixed.fix(&rules);
+            }
+            assert_eq!(ordered.0, fixed.0);
+        }
+    }
+}
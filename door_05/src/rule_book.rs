@@ -0,0 +1,248 @@
+use crate::{Pages, Rule};
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, Write};
+
+/// Rules and updates that stay loaded, so rules can be changed and only the updates
+/// containing both pages of the changed rule are validated again.
+pub struct RuleBook {
+    rules: HashMap<i32, Rule>,
+    updates: Vec<Pages>,
+    valid: Vec<bool>,
+    updates_by_page: HashMap<i32, Vec<usize>>,
+}
+
+impl RuleBook {
+    pub fn new(rules: HashMap<i32, Rule>, updates: Vec<Pages>) -> Self {
+        let valid = updates.iter().map(|u| u.is_correct(&rules)).collect();
+        let mut updates_by_page: HashMap<i32, Vec<usize>> = HashMap::new();
+        for (index, update) in updates.iter().enumerate() {
+            let pages: HashSet<&i32> = update.0.iter().collect();
+            for page in pages {
+                updates_by_page.entry(*page).or_default().push(index);
+            }
+        }
+
+        Self {
+            rules,
+            updates,
+            valid,
+            updates_by_page,
+        }
+    }
+
+    pub fn updates(&self) -> &[Pages] {
+        &self.updates
+    }
+
+    pub fn is_valid(&self, update: usize) -> bool {
+        self.valid[update]
+    }
+
+    pub fn has_rule(&self, before: i32, after: i32) -> bool {
+        self.rules
+            .get(&before)
+            .map(|rule| rule.before.contains(&after))
+            .unwrap_or(false)
+    }
+
+    fn affected(&self, before: i32, after: i32) -> Vec<usize> {
+        let (Some(with_before), Some(with_after)) = (
+            self.updates_by_page.get(&before),
+            self.updates_by_page.get(&after),
+        ) else {
+            return Vec::new();
+        };
+        let with_after: HashSet<&usize> = with_after.iter().collect();
+        with_before
+            .iter()
+            .filter(|u| with_after.contains(u))
+            .copied()
+            .collect()
+    }
+
+    // Validates the given updates again and returns those whose validity changed.
+    fn revalidate(&mut self, updates: Vec<usize>) -> Vec<usize> {
+        let mut changed = Vec::new();
+        for update in updates {
+            let valid = self.updates[update].is_correct(&self.rules);
+            if valid != self.valid[update] {
+                self.valid[update] = valid;
+                changed.push(update);
+            }
+        }
+        changed
+    }
+
+    pub fn add_rule(&mut self, before: i32, after: i32) -> Vec<usize> {
+        self.rules
+            .entry(before)
+            .or_insert(Rule::new(before))
+            .add(after);
+        self.revalidate(self.affected(before, after))
+    }
+
+    pub fn remove_rule(&mut self, before: i32, after: i32) -> Vec<usize> {
+        let Some(rule) = self.rules.get_mut(&before) else {
+            return Vec::new();
+        };
+        if !rule.before.remove(&after) {
+            return Vec::new();
+        }
+        self.revalidate(self.affected(before, after))
+    }
+
+    /// The currently valid updates that adding `before|after` would make invalid.
+    pub fn invalidated_by(&self, before: i32, after: i32) -> Vec<usize> {
+        self.affected(before, after)
+            .into_iter()
+            .filter(|u| self.valid[*u])
+            .filter(|u| {
+                let pages = &self.updates[*u].0;
+                let before_index = pages.iter().position(|p| *p == before);
+                let after_index = pages.iter().position(|p| *p == after);
+                after_index < before_index
+            })
+            .collect()
+    }
+
+    pub fn middle_sum(&self) -> i32 {
+        self.updates
+            .iter()
+            .zip(&self.valid)
+            .filter(|(_, valid)| **valid)
+            .map(|(update, _)| update.get_middle())
+            .sum()
+    }
+}
+
+fn parse_rule(rule: &str) -> Option<(i32, i32)> {
+    let (before, after) = rule.trim().split_once('|')?;
+    Some((before.parse().ok()?, after.parse().ok()?))
+}
+
+fn format_updates(updates: &[usize]) -> String {
+    if updates.is_empty() {
+        return String::from("none");
+    }
+    updates
+        .iter()
+        .map(|u| (u + 1).to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Reads one command per line until `quit` or the end of the input. Updates are numbered
+/// from 1 like the lines of the update section.
+pub fn run<R: BufRead, W: Write>(
+    book: &mut RuleBook,
+    input: R,
+    mut output: W,
+) -> std::io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        let (command, argument) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+
+        match (command, parse_rule(argument)) {
+            ("", _) => {}
+            ("quit", _) => break,
+            ("add", Some((before, after))) => {
+                let changed = book.add_rule(before, after);
+                writeln!(output, "became invalid: {}", format_updates(&changed))?;
+            }
+            ("remove", Some((before, after))) => {
+                if !book.has_rule(before, after) {
+                    writeln!(output, "no rule {before}|{after}")?;
+                    continue;
+                }
+                let changed = book.remove_rule(before, after);
+                writeln!(output, "became valid: {}", format_updates(&changed))?;
+            }
+            ("check", Some((before, after))) => {
+                let invalidated = book.invalidated_by(before, after);
+                writeln!(
+                    output,
+                    "would become invalid: {}",
+                    format_updates(&invalidated)
+                )?;
+            }
+            ("status", _) => {
+                let valid = (0..book.updates().len())
+                    .filter(|u| book.is_valid(*u))
+                    .count();
+                writeln!(
+                    output,
+                    "{valid} of {} updates valid, middle sum {}",
+                    book.updates().len(),
+                    book.middle_sum()
+                )?;
+            }
+            ("show", _) => match argument.trim().parse::<usize>() {
+                Ok(number) if number >= 1 && number <= book.updates().len() => {
+                    let state = if book.is_valid(number - 1) {
+                        "valid"
+                    } else {
+                        "invalid"
+                    };
+                    writeln!(output, "{:?} {state}", book.updates()[number - 1].0)?;
+                }
+                _ => writeln!(output, "unknown update {}", argument.trim())?,
+            },
+            _ => writeln!(
+                output,
+                "commands: add X|Y, remove X|Y, check X|Y, show N, status, quit"
+            )?,
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::rules;
+
+    fn book() -> RuleBook {
+        let rules = rules(&[(1, 2), (2, 3)]);
+        let updates = vec![
+            Pages(vec![1, 2, 3]),
+            Pages(vec![3, 4, 1]),
+            Pages(vec![4, 5, 6]),
+        ];
+        RuleBook::new(rules, updates)
+    }
+
+    #[test]
+    fn test_add_and_remove() {
+        let mut book = book();
+        assert!(book.is_valid(0) && book.is_valid(1) && book.is_valid(2));
+
+        assert_eq!(book.invalidated_by(1, 3), vec![1]);
+        assert_eq!(book.add_rule(1, 3), vec![1]);
+        assert!(!book.is_valid(1));
+        assert_eq!(book.add_rule(6, 4), vec![2]);
+        assert_eq!(book.middle_sum(), 2);
+
+        assert_eq!(book.remove_rule(1, 3), vec![1]);
+        assert!(book.is_valid(1));
+        assert_eq!(book.remove_rule(1, 3), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_commands() {
+        let mut book = book();
+        let input = "check 1|3\nadd 1|3\nstatus\nshow 2\nremove 7|8\nremove 1|3\nquit\nstatus\n";
+        let mut output = Vec::new();
+        run(&mut book, input.as_bytes(), &mut output).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "would become invalid: 2\n\
+             became invalid: 2\n\
+             2 of 3 updates valid, middle sum 7\n\
+             [3, 4, 1] invalid\n\
+             no rule 7|8\n\
+             became valid: 2\n"
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use crate::{Pages, Rule};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+/// A rule `before|after` broken by an update, with the indices of both pages in it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    pub before: i32,
+    pub after: i32,
+    pub before_index: usize,
+    pub after_index: usize,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}|{} violated: {} at position {}, {} at position {}",
+            self.before, self.after, self.before, self.before_index, self.after, self.after_index
+        )
+    }
+}
+
+impl Pages {
+    pub fn violations(&self, rules: &HashMap<i32, Rule>) -> Vec<Violation> {
+        let mut violations = Vec::new();
+
+        for (index, page) in self.0.iter().enumerate() {
+            let Some(page_rule) = rules.get(page) else {
+                continue;
+            };
+
+            for (read_index, other) in self.0[..index].iter().enumerate() {
+                if page_rule.before.contains(other) {
+                    violations.push(Violation {
+                        before: *page,
+                        after: *other,
+                        before_index: index,
+                        after_index: read_index,
+                    });
+                }
+            }
+        }
+
+        violations
+    }
+}
+
+/// Counts how often every rule is violated over all updates, most frequent first.
+pub fn violation_summary(
+    updates: &[Pages],
+    rules: &HashMap<i32, Rule>,
+) -> Vec<((i32, i32), usize)> {
+    let mut counts: HashMap<(i32, i32), usize> = HashMap::new();
+    for update in updates {
+        for violation in update.violations(rules) {
+            *counts
+                .entry((violation.before, violation.after))
+                .or_insert(0) += 1;
+        }
+    }
+
+    let mut summary: Vec<_> = counts.into_iter().collect();
+    summary.sort_by(|(rule, count), (other_rule, other_count)| {
+        other_count.cmp(count).then(rule.cmp(other_rule))
+    });
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::rules;
+
+    fn example() -> (HashMap<i32, Rule>, Vec<Pages>) {
+        let rules = rules(&[
+            (47, 53),
+            (97, 13),
+            (97, 61),
+            (97, 47),
+            (75, 29),
+            (61, 13),
+            (75, 53),
+            (29, 13),
+            (97, 29),
+            (53, 29),
+            (61, 53),
+            (97, 53),
+            (61, 29),
+            (47, 13),
+            (75, 47),
+            (97, 75),
+            (47, 61),
+            (75, 61),
+            (47, 29),
+            (75, 13),
+            (53, 13),
+        ]);
+        let updates = [
+            vec![75, 47, 61, 53, 29],
+            vec![75, 97, 47, 61, 53],
+            vec![61, 13, 29],
+            vec![97, 13, 75, 29, 47],
+        ]
+        .into_iter()
+        .map(Pages)
+        .collect();
+        (rules, updates)
+    }
+
+    #[test]
+    fn test_violations() {
+        let (rules, updates) = example();
+
+        assert!(updates[0].violations(&rules).is_empty());
+        assert_eq!(
+            updates[1].violations(&rules),
+            vec![Violation {
+                before: 97,
+                after: 75,
+                before_index: 1,
+                after_index: 0
+            }]
+        );
+        assert_eq!(
+            updates[2].violations(&rules)[0].to_string(),
+            "29|13 violated: 29 at position 2, 13 at position 1"
+        );
+        assert_eq!(updates[3].violations(&rules).len(), 4);
+    }
+
+    #[test]
+    fn test_violations_agree_with_is_correct() {
+        let (rules, updates) = example();
+        for update in updates {
+            assert_eq!(
+                update.violations(&rules).is_empty(),
+                update.is_correct(&rules)
+            );
+        }
+    }
+
+    #[test]
+    fn test_summary() {
+        let (rules, updates) = example();
+        let summary = violation_summary(&updates, &rules);
+
+        assert_eq!(summary.iter().map(|(_, count)| count).sum::<usize>(), 6);
+        assert_eq!(summary[0], ((29, 13), 2));
+    }
+}
@@ -0,0 +1,283 @@
+use crate::rules::Rules;
+use crate::walk::{self, WalkOutcome};
+use crate::{Direction, Field, Guard, Map, Position};
+use std::collections::HashSet;
+use std::io::{BufRead, Write};
+
+const CLEAR: &str = "\x1b[2J\x1b[H";
+const TRAIL: &str = "\x1b[33m";
+const PLACED: &str = "\x1b[1;31m";
+const GUARD: &str = "\x1b[1;32m";
+const RESET: &str = "\x1b[0m";
+
+/// Steps through a recorded patrol. The whole run is recorded up front, so moving
+/// backwards is as cheap as moving forwards.
+pub struct Debugger<'a> {
+    map: &'a Map,
+    obstacle: Option<Position>,
+    path: Vec<(Position, Direction)>,
+    outcome: WalkOutcome,
+    step: usize,
+}
+
+impl<'a> Debugger<'a> {
+    pub fn new(map: &'a Map, guard: &Guard, rules: &Rules, obstacle: Option<Position>) -> Self {
+        let (path, outcome) = walk::trace(map, rules, guard, obstacle.as_ref());
+        Self {
+            map,
+            obstacle,
+            path,
+            outcome,
+            step: 0,
+        }
+    }
+
+    fn last(&self) -> usize {
+        self.path.len() - 1
+    }
+
+    pub fn forward(&mut self, steps: usize) {
+        self.step = (self.step + steps).min(self.last());
+    }
+
+    pub fn back(&mut self, steps: usize) {
+        self.step = self.step.saturating_sub(steps);
+    }
+
+    pub fn jump(&mut self, step: usize) {
+        self.step = step.min(self.last());
+    }
+
+    /// Moves forward to the next state facing a new direction.
+    pub fn run_to_turn(&mut self) {
+        let direction = &self.path[self.step].1;
+        self.step = (self.step + 1..=self.last())
+            .find(|step| self.path[*step].1 != *direction)
+            .unwrap_or(self.last());
+    }
+
+    /// Moves to the last state before the guard repeats itself, or leaves the map when it
+    /// does not walk in a loop.
+    pub fn run_to_loop(&mut self) {
+        self.step = self.last();
+    }
+
+    pub fn status(&self) -> String {
+        self.status_at(self.step)
+    }
+
+    fn status_at(&self, step: usize) -> String {
+        let (position, direction) = &self.path[step];
+        let end = match &self.outcome {
+            WalkOutcome::Exits { exit, .. } => format!("exits at ({}, {})", exit.x, exit.y),
+            WalkOutcome::Loops { cycle, .. } => {
+                format!("loops back to step {}", self.path.len() - cycle.len())
+            }
+        };
+        format!(
+            "step {}/{} at ({}, {}) facing {:?}, {}",
+            step,
+            self.last(),
+            position.x,
+            position.y,
+            direction,
+            end
+        )
+    }
+
+    /// The map with the guard, its trail up to the current step and the placed obstacle.
+    pub fn render(&self, colour: bool) -> String {
+        self.frame(self.step, colour)
+    }
+
+    fn frame(&self, step: usize, colour: bool) -> String {
+        let trail: HashSet<&Position> = self.path[..=step]
+            .iter()
+            .map(|(position, _)| position)
+            .collect();
+        let (guard, direction) = &self.path[step];
+        let paint = |style: &str, glyph: char| {
+            if colour {
+                format!("{style}{glyph}{RESET}")
+            } else {
+                glyph.to_string()
+            }
+        };
+
+        let mut output = String::new();
+        for (y, row) in self.map.map.iter().enumerate() {
+            for (x, field) in row.iter().enumerate() {
+                let position = Position {
+                    x: x as i32,
+                    y: y as i32,
+                };
+                let cell = if position == *guard {
+                    let glyph = match direction {
+                        Direction::Up => '^',
+                        Direction::Right => '>',
+                        Direction::Down => 'v',
+                        Direction::Left => '<',
+                        diagonal => diagonal.arrow(),
+                    };
+                    paint(GUARD, glyph)
+                } else if self.obstacle.as_ref() == Some(&position) {
+                    paint(PLACED, 'O')
+                } else if let Field::Obstacle = field {
+                    String::from("#")
+                } else if trail.contains(&position) {
+                    paint(TRAIL, 'X')
+                } else if let Field::OneWay(direction) = field {
+                    direction.arrow().to_string()
+                } else {
+                    String::from(".")
+                };
+                output.push_str(&cell);
+            }
+            output.push('\n');
+        }
+        output.push_str(&self.status_at(step));
+        output.push('\n');
+        output
+    }
+
+    /// The whole run as an asciicast v2 recording, one frame every `frame_time` seconds.
+    pub fn asciicast(&self, frame_time: f64) -> String {
+        let mut cast = format!(
+            "{{\"version\": 2, \"width\": {}, \"height\": {}}}\n",
+            self.map.width.max(80),
+            self.map.height + 2
+        );
+        for step in 0..=self.last() {
+            let frame = format!("{CLEAR}{}", self.frame(step, true)).replace('\n', "\r\n");
+            cast.push_str(&format!(
+                "[{:.3}, \"o\", \"{}\"]\n",
+                step as f64 * frame_time,
+                json_escape(&frame)
+            ));
+        }
+
+        cast
+    }
+}
+
+fn json_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Reads commands from `input` and redraws the map after each one:
+/// `s [N]` step, `b [N]` step back, `t` run to the next turn, `l` run to the loop or exit,
+/// `g N` jump to step N and `q` quit.
+pub fn run<R: BufRead, W: Write>(
+    debugger: &mut Debugger,
+    input: R,
+    mut output: W,
+    colour: bool,
+) -> std::io::Result<()> {
+    write!(output, "{CLEAR}{}", debugger.render(colour))?;
+
+    for line in input.lines() {
+        let line = line?;
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or("s");
+        let count = words.next().and_then(|n| n.parse::<usize>().ok());
+
+        match command {
+            "s" | "step" => debugger.forward(count.unwrap_or(1)),
+            "b" | "back" => debugger.back(count.unwrap_or(1)),
+            "t" | "turn" => debugger.run_to_turn(),
+            "l" | "loop" => debugger.run_to_loop(),
+            "g" | "goto" => debugger.jump(count.unwrap_or(0)),
+            "q" | "quit" => break,
+            _ => {
+                writeln!(output, "commands: s [N], b [N], t, l, g N, q")?;
+                continue;
+            }
+        }
+
+        write!(output, "{CLEAR}{}", debugger.render(colour))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read_file;
+
+    #[test]
+    fn test_navigation() {
+        let (map, guards) = read_file("input_test.txt");
+        let mut debugger = Debugger::new(&map, &guards[0], &Rules::default(), None);
+
+        debugger.run_to_turn();
+        assert_eq!(debugger.step, 6);
+        assert!(debugger
+            .status()
+            .starts_with("step 6/54 at (4, 1) facing Right"));
+        debugger.back(10);
+        assert_eq!(debugger.step, 0);
+        debugger.jump(100);
+        assert_eq!(debugger.step, 54);
+        assert!(debugger.status().ends_with("exits at (7, 9)"));
+    }
+
+    #[test]
+    fn test_render() {
+        let (map, guards) = crate::read_map(["..#.", "....", ".^.."]);
+        let mut debugger = Debugger::new(
+            &map,
+            &guards[0],
+            &Rules::default(),
+            Some(Position { x: 1, y: 0 }),
+        );
+        debugger.forward(2);
+
+        assert_eq!(
+            debugger.render(false),
+            ".O#.\n.>..\n.X..\nstep 2/4 at (1, 1) facing Right, exits at (3, 1)\n"
+        );
+    }
+
+    #[test]
+    fn test_commands_and_asciicast() {
+        let (map, guards) = read_file("input_test.txt");
+        let mut debugger = Debugger::new(
+            &map,
+            &guards[0],
+            &Rules::default(),
+            Some(Position { x: 3, y: 6 }),
+        );
+
+        let mut output = Vec::new();
+        run(
+            &mut debugger,
+            "l\nb 2\nq\ns\n".as_bytes(),
+            &mut output,
+            false,
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.matches(CLEAR).count(), 3);
+        assert!(output.contains("loops back to step 0"));
+        assert_eq!(debugger.step, debugger.last() - 2);
+
+        let cast = debugger.asciicast(0.1);
+        let lines: Vec<&str> = cast.lines().collect();
+        assert_eq!(lines[0], "{\"version\": 2, \"width\": 80, \"height\": 12}");
+        assert_eq!(lines.len(), debugger.last() + 2);
+        assert!(lines[1].starts_with("[0.000, \"o\", \"\\u001b[2J\\u001b[H"));
+        assert!(lines[2].starts_with("[0.100, \"o\", "));
+    }
+}
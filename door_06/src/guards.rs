@@ -0,0 +1,171 @@
+use crate::patrol::Patrol;
+use crate::rules::Rules;
+use crate::{Direction, Guard, Map, Position};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Semantics {
+    /// Every guard walks as if it were alone on the map.
+    Independent,
+    /// Guards move one after another each tick and turn in front of another guard like in
+    /// front of an obstacle.
+    CollisionAware,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Fate {
+    /// The guard left the map from `at` in tick `tick`, counting from 0.
+    Exited {
+        at: Position,
+        tick: usize,
+    },
+    Looping,
+}
+
+pub struct Simulation {
+    pub fates: Vec<Fate>,
+    /// Cells stood on by any guard.
+    pub visited: HashSet<Position>,
+}
+
+pub fn simulate(map: &Map, guards: &[Guard], rules: &Rules, semantics: Semantics) -> Simulation {
+    match semantics {
+        Semantics::Independent => independent(map, guards, rules),
+        Semantics::CollisionAware => collision_aware(map, guards, rules),
+    }
+}
+
+fn independent(map: &Map, guards: &[Guard], rules: &Rules) -> Simulation {
+    let mut visited = HashSet::new();
+    let mut fates = Vec::new();
+
+    for guard in guards {
+        let patrol = Patrol::run(map, guard, rules);
+        visited.extend(patrol.path.iter().map(|(position, _)| position.clone()));
+        fates.push(match patrol.exit {
+            Some(at) => Fate::Exited {
+                at,
+                tick: patrol.path.len() - 1,
+            },
+            None => Fate::Looping,
+        });
+    }
+
+    Simulation { fates, visited }
+}
+
+fn collision_aware(map: &Map, guards: &[Guard], rules: &Rules) -> Simulation {
+    let mut guards: Vec<Option<Guard>> = guards.iter().cloned().map(Some).collect();
+    let mut fates: Vec<Option<Fate>> = guards.iter().map(|_| None).collect();
+    let mut visited: HashSet<Position> = guards.iter().flatten().map(|g| g.pos.clone()).collect();
+    let mut seen: HashSet<Vec<Option<(Position, Direction)>>> = HashSet::new();
+    let mut tick = 0;
+
+    while guards.iter().any(Option::is_some) {
+        let state = guards
+            .iter()
+            .map(|g| g.as_ref().map(|g| (g.pos.clone(), g.direction.clone())))
+            .collect();
+        if !seen.insert(state) {
+            break;
+        }
+
+        for index in 0..guards.len() {
+            let Some(guard) = &guards[index] else {
+                continue;
+            };
+            let Some(next) = rules.ahead(map, guard) else {
+                fates[index] = Some(Fate::Exited {
+                    at: guard.pos.clone(),
+                    tick,
+                });
+                guards[index] = None;
+                continue;
+            };
+
+            let occupied = guards.iter().flatten().any(|other| other.pos == next);
+            let guard = guards[index].as_mut().unwrap();
+            if occupied || rules.is_blocked(map, &next, &guard.direction, None) {
+                rules.turn(&mut guard.direction);
+            } else {
+                guard.pos = next.clone();
+                visited.insert(next);
+            }
+        }
+
+        tick += 1;
+    }
+
+    Simulation {
+        fates: fates
+            .into_iter()
+            .map(|fate| fate.unwrap_or(Fate::Looping))
+            .collect(),
+        visited,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read_map;
+
+    #[test]
+    fn test_glyphs() {
+        let (_, guards) = read_map(["^.>", "v.<"]);
+        let directions: Vec<Direction> = guards.iter().map(|g| g.direction.clone()).collect();
+        assert_eq!(
+            directions,
+            vec![
+                Direction::Up,
+                Direction::Right,
+                Direction::Down,
+                Direction::Left
+            ]
+        );
+    }
+
+    #[test]
+    fn test_independent_matches_patrol() {
+        let (map, guards) = crate::read_file("input_test.txt");
+        let simulation = simulate(&map, &guards, &Rules::default(), Semantics::Independent);
+        assert_eq!(simulation.visited.len(), 41);
+        assert_eq!(
+            simulation.fates,
+            vec![Fate::Exited {
+                at: Position { x: 7, y: 9 },
+                tick: 44 + 10
+            }]
+        );
+
+        let alone = simulate(&map, &guards, &Rules::default(), Semantics::CollisionAware);
+        assert_eq!(alone.fates, simulation.fates);
+        assert_eq!(alone.visited, simulation.visited);
+    }
+
+    #[test]
+    fn test_collision() {
+        // two guards walking towards each other along the same row
+        let (map, guards) = read_map([".....", ">...<", "....."]);
+
+        let independent = simulate(&map, &guards, &Rules::default(), Semantics::Independent);
+        assert_eq!(independent.visited.len(), 5);
+
+        let colliding = simulate(&map, &guards, &Rules::default(), Semantics::CollisionAware);
+        // they meet in the middle, the right one turns up and the left one down
+        assert_eq!(
+            colliding.fates,
+            vec![
+                Fate::Exited {
+                    at: Position { x: 2, y: 2 },
+                    tick: 4
+                },
+                Fate::Exited {
+                    at: Position { x: 3, y: 0 },
+                    tick: 3
+                }
+            ]
+        );
+        assert_eq!(colliding.visited.len(), 7);
+    }
+}
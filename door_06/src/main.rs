@@ -1,43 +1,138 @@
+mod debugger;
+mod guards;
+mod patrol;
+mod rules;
+mod search;
+mod walk;
+
 use common::read_lines;
+use guards::Semantics;
+use patrol::Patrol;
+use rayon::prelude::*;
+use rules::{Rules, Turn};
 use std::collections::HashSet;
 
 fn main() {
-    let (mut start_map, guard) = read_file("door_06/input.txt");
-    let mut start_guard = guard.expect("Some guard");
+    let path = std::env::args()
+        .find_map(|arg| arg.strip_prefix("--map=").map(String::from))
+        .unwrap_or(String::from("door_06/input.txt"));
+    let (start_map, guards) = read_file(&path);
+    let start_guard = guards.first().expect("Some guard").clone();
+
+    let rules = read_rules();
+    if std::env::args().any(|arg| arg == "--debug" || arg.starts_with("--asciicast=")) {
+        debug(&start_map, &start_guard, &rules);
+        return;
+    }
 
-    let mut stuck_counter = 0;
+    if rules != Rules::default() {
+        println!("{:?}", rules);
+    }
+    let patrol = Patrol::run(&start_map, &start_guard, &rules);
+    if std::env::args().any(|arg| arg == "--print") {
+        print(&patrol.mark_visited(&start_map));
+    }
 
-    for y in (0..start_map.height) {
-        for x in (0..start_map.width) {
-            let mut map = start_map.clone();
-            let mut guard = start_guard.clone();
+    let stuck_counter = if std::env::args().any(|arg| arg == "--brute-force") {
+        loop_obstacles_brute_force(&start_map, &start_guard, &rules).len()
+    } else {
+        patrol.loop_obstacles(&start_map).len()
+    };
 
-            if x == guard.pos.x && y == guard.pos.y {
-                continue;
-            }
+    println!("visited: {}", patrol.visited());
+    println!("turns: {}, exit: {:?}", patrol.turns.len(), patrol.exit);
+    println!("stuck_counter: {}", stuck_counter);
 
-            {
-                let field = map.get_field_mut(x, y).unwrap();
-                match field {
-                    Field::Cell(visited) => *field = Field::Obstacle(None),
-                    Field::Obstacle(_) => {
-                        continue;
-                    }
-                }
-            }
+    if guards.len() > 1 {
+        let semantics = if std::env::args().any(|arg| arg == "--collide") {
+            Semantics::CollisionAware
+        } else {
+            Semantics::Independent
+        };
+        let simulation = guards::simulate(&start_map, &guards, &rules, semantics);
+        println!(
+            "{} guards ({:?}) visited: {}",
+            guards.len(),
+            semantics,
+            simulation.visited.len()
+        );
+        for (index, fate) in simulation.fates.iter().enumerate() {
+            println!("  guard {}: {:?}", index + 1, fate);
+        }
+    }
+}
 
-            while guard.is_in_map(&map) {
-                //print(&map);
+// door_06 [--turn=right|left|around] [--diagonal] [--wrap] [--one-way]
+fn read_rules() -> Rules {
+    let turn = match std::env::args()
+        .find_map(|arg| arg.strip_prefix("--turn=").map(String::from))
+        .as_deref()
+    {
+        None | Some("right") => Turn::Right,
+        Some("left") => Turn::Left,
+        Some("around") => Turn::Around,
+        Some(other) => panic!("unknown turn {}", other),
+    };
+    Rules {
+        turn,
+        diagonal: std::env::args().any(|arg| arg == "--diagonal"),
+        wrap: std::env::args().any(|arg| arg == "--wrap"),
+        one_way: std::env::args().any(|arg| arg == "--one-way"),
+    }
+}
 
-                if update(&mut map, &mut guard) {
-                    stuck_counter += 1;
-                    break;
-                }
+// door_06 --debug [--obstacle=X,Y] [--asciicast=FILE]
+fn debug(map: &Map, guard: &Guard, rules: &Rules) {
+    let obstacle = std::env::args()
+        .find_map(|arg| arg.strip_prefix("--obstacle=").map(String::from))
+        .map(|arg| {
+            let (x, y) = arg.split_once(',').expect("obstacle as X,Y");
+            Position {
+                x: x.parse().expect("obstacle x"),
+                y: y.parse().expect("obstacle y"),
             }
-        }
+        });
+    let mut debugger = debugger::Debugger::new(map, guard, rules, obstacle);
+
+    if let Some(path) =
+        std::env::args().find_map(|arg| arg.strip_prefix("--asciicast=").map(String::from))
+    {
+        std::fs::write(&path, debugger.asciicast(0.05)).expect("unable to write asciicast");
+        println!("wrote {}", path);
+        return;
     }
 
-    println!("stuck_counter: {}", stuck_counter);
+    debugger::run(
+        &mut debugger,
+        std::io::stdin().lock(),
+        std::io::stdout(),
+        true,
+    )
+    .expect("unable to read commands");
+    println!("{}", debugger.status());
+}
+
+fn loop_obstacles_brute_force(
+    start_map: &Map,
+    start_guard: &Guard,
+    rules: &Rules,
+) -> Vec<Position> {
+    (0..start_map.height)
+        .into_par_iter()
+        .flat_map(|y| {
+            (0..start_map.width)
+                .into_par_iter()
+                .map(move |x| Position { x, y })
+        })
+        .filter(|position| *position != start_guard.pos)
+        .filter(|position| {
+            !matches!(
+                start_map.map[position.y as usize][position.x as usize],
+                Field::Obstacle
+            )
+        })
+        .filter(|position| walk::walk(start_map, rules, start_guard, Some(position)).is_loop())
+        .collect()
 }
 
 fn check_ob_pos(ob_pos: Position, mut map: Map, mut guard: Guard) {}
@@ -54,46 +149,14 @@ fn print(map: &Map) {
                         row.push('.')
                     }
                 }
-                Field::Obstacle(_) => row.push('#'),
+                Field::Obstacle => row.push('#'),
+                Field::OneWay(direction) => row.push(direction.arrow()),
             }
         }
         println!("{}", row);
     }
 }
 
-fn update(map: &mut Map, guard: &mut Guard) -> bool {
-    let next_pos = guard.get_next_position();
-    if map.is_out_of_bounds(&next_pos) {
-        guard.move_to_next()
-    } else {
-        let next_field = &mut map.get_field_mut(next_pos.x, next_pos.y).unwrap();
-
-        match next_field {
-            Field::Cell(visited) => {
-                guard.move_to_next();
-                *visited = true;
-            }
-            Field::Obstacle(hit_from) => {
-                let dir = guard.direction.clone();
-                match hit_from {
-                    None => *hit_from = Some(dir),
-                    Some(direction) => {
-                        if *direction == dir {
-                            return true;
-                        } else {
-                            *hit_from = Some(dir)
-                        }
-                    }
-                }
-
-                guard.rotate_right();
-            }
-        }
-    }
-
-    false
-}
-
 #[derive(Clone)]
 struct Map {
     pub map: Vec<Vec<Field>>,
@@ -124,11 +187,6 @@ impl Map {
         Map { map, width, height }
     }
 
-    fn get_field_mut(&mut self, x: i32, y: i32) -> Option<&mut Field> {
-        let mut rows: &mut Vec<Field> = self.map.get_mut(y as usize).expect("expect row");
-        rows.get_mut(x as usize)
-    }
-
     fn is_out_of_bounds(&self, pos: &Position) -> bool {
         if pos.x >= 0 && pos.x < self.width && pos.y >= 0 && pos.y < self.height {
             return false;
@@ -141,10 +199,13 @@ impl Map {
 #[derive(Clone)]
 enum Field {
     Cell(bool),
-    Obstacle(Option<Direction>),
+    Obstacle,
+    /// A cell that can only be entered moving in the given direction, when the rules
+    /// enable one-way tiles.
+    OneWay(Direction),
 }
 
-#[derive(Clone, PartialEq, PartialOrd, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Hash)]
 struct Position {
     pub x: i32,
     pub y: i32,
@@ -155,21 +216,80 @@ struct Dir {
     pub y: i32,
 }
 
-#[derive(Clone, PartialEq, PartialOrd, Eq, Hash)]
+/// The eight headings of the guard. The puzzle only uses the four straight ones, the
+/// diagonal ones come with `Rules::diagonal`.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Hash)]
 enum Direction {
     Up,
+    UpRight,
     Right,
+    DownRight,
     Down,
+    DownLeft,
     Left,
+    UpLeft,
 }
 
 impl Direction {
+    /// Clockwise, starting with up.
+    pub const HEADINGS: [Direction; 8] = [
+        Direction::Up,
+        Direction::UpRight,
+        Direction::Right,
+        Direction::DownRight,
+        Direction::Down,
+        Direction::DownLeft,
+        Direction::Left,
+        Direction::UpLeft,
+    ];
+
+    /// Position in `HEADINGS`.
+    pub fn index(&self) -> usize {
+        match self {
+            Direction::Up => 0,
+            Direction::UpRight => 1,
+            Direction::Right => 2,
+            Direction::DownRight => 3,
+            Direction::Down => 4,
+            Direction::DownLeft => 5,
+            Direction::Left => 6,
+            Direction::UpLeft => 7,
+        }
+    }
+
+    /// The heading `eighths` eighths of a turn clockwise.
+    pub fn rotated(&self, eighths: usize) -> Self {
+        Self::HEADINGS[(self.index() + eighths) % 8].clone()
+    }
+
+    pub fn delta(&self) -> (i32, i32) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::UpRight => (1, -1),
+            Direction::Right => (1, 0),
+            Direction::DownRight => (1, 1),
+            Direction::Down => (0, 1),
+            Direction::DownLeft => (-1, 1),
+            Direction::Left => (-1, 0),
+            Direction::UpLeft => (-1, -1),
+        }
+    }
+
     pub fn rotate_right(&mut self) {
+        *self = self.rotated(2);
+    }
+
+    /// The glyph of a one-way tile in this direction.
+    pub fn arrow(&self) -> char {
         match self {
-            Direction::Up => *self = Self::Right,
-            Direction::Right => *self = Self::Down,
-            Direction::Down => *self = Self::Left,
-            Direction::Left => *self = Self::Up,
+            Direction::Up => '↑',
+            Direction::UpRight => '↗',
+            Direction::Right => '→',
+            Direction::DownRight => '↘',
+            Direction::Down => '↓',
+            Direction::DownLeft => '↙',
+            Direction::Left => '←',
+            Direction::UpLeft => '↖',
         }
     }
 }
@@ -183,44 +303,16 @@ struct Guard {
 
 impl Guard {
     pub(crate) fn get_next_position(&self) -> Position {
-        let mut current_x = self.pos.x;
-        let mut current_y = self.pos.y;
-        match &self.direction {
-            Direction::Up => {
-                current_y -= 1;
-            }
-            Direction::Down => {
-                current_y += 1;
-            }
-            Direction::Right => {
-                current_x += 1;
-            }
-            Direction::Left => {
-                current_x -= 1;
-            }
-        }
+        let (dx, dy) = self.direction.delta();
         Position {
-            x: current_x,
-            y: current_y,
+            x: self.pos.x + dx,
+            y: self.pos.y + dy,
         }
     }
 
     pub(crate) fn get_current_pos(&self) -> Position {
         self.pos.clone()
     }
-
-    pub fn move_to_next(&mut self) {
-        let next = self.get_next_position();
-        self.pos = next;
-    }
-
-    pub fn rotate_right(&mut self) {
-        self.direction.rotate_right();
-    }
-
-    pub fn is_in_map(&self, map: &Map) -> bool {
-        !map.is_out_of_bounds(&self.pos)
-    }
 }
 
 impl Guard {
@@ -244,25 +336,42 @@ impl Guard {
     }
 }
 
-fn read_file(path: &str) -> (Map, Option<Guard>) {
-    let mut map: Vec<Vec<Field>> = Vec::new();
-    let mut guard: Option<Guard> = None;
+fn read_file(path: &str) -> (Map, Vec<Guard>) {
     let lines = read_lines(path).expect(&format!("Failed to read {}", path));
-    for (y, line) in lines.enumerate() {
-        let line = line.expect("Failed to read line");
+    read_map(lines.map(|line| line.expect("Failed to read line")))
+}
+
+fn read_map<I, S>(lines: I) -> (Map, Vec<Guard>)
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut map: Vec<Vec<Field>> = Vec::new();
+    let mut guards: Vec<Guard> = Vec::new();
+    for (y, line) in lines.into_iter().enumerate() {
         let mut l = Vec::new();
-        for (x, field) in line.chars().enumerate() {
+        for (x, field) in line.as_ref().chars().enumerate() {
             match field {
-                '#' => l.push(Field::Obstacle(None)),
-                '^' => {
+                '#' => l.push(Field::Obstacle),
+                '^' | '>' | 'v' | '<' => {
+                    let direction = match field {
+                        '^' => Direction::Up,
+                        '>' => Direction::Right,
+                        'v' => Direction::Down,
+                        _ => Direction::Left,
+                    };
                     l.push(Field::Cell(true));
-                    guard = Some(Guard::new(x as i32, y as i32, Direction::Up))
+                    guards.push(Guard::new(x as i32, y as i32, direction))
                 }
+                '↑' => l.push(Field::OneWay(Direction::Up)),
+                '→' => l.push(Field::OneWay(Direction::Right)),
+                '↓' => l.push(Field::OneWay(Direction::Down)),
+                '←' => l.push(Field::OneWay(Direction::Left)),
                 _ => l.push(Field::Cell(false)),
             }
         }
         map.push(l);
     }
 
-    (Map::new(map), guard)
+    (Map::new(map), guards)
 }
@@ -0,0 +1,110 @@
+use crate::rules::Rules;
+use crate::walk::{self, WalkOutcome};
+use crate::{search, Direction, Field, Guard, Map, Position};
+
+/// A single run of the guard over the unchanged map.
+pub struct Patrol {
+    /// Every state of the guard in order, starting with the initial one. A turn on the spot
+    /// adds a state with the same position.
+    pub path: Vec<(Position, Direction)>,
+    /// Positions where the guard turned, in order.
+    pub turns: Vec<Position>,
+    /// The last position on the map before the guard left it, `None` if it walks in a loop.
+    pub exit: Option<Position>,
+    visited: usize,
+    rules: Rules,
+    guard: Guard,
+}
+
+impl Patrol {
+    pub fn run(map: &Map, guard: &Guard, rules: &Rules) -> Self {
+        let (path, outcome) = walk::trace(map, rules, guard, None);
+        let (visited, exit, turns) = match outcome {
+            WalkOutcome::Exits {
+                visited,
+                exit,
+                turns,
+                ..
+            } => (visited, Some(exit), turns),
+            WalkOutcome::Loops { visited, turns, .. } => (visited, None, turns),
+        };
+        Self {
+            path,
+            turns,
+            exit,
+            visited,
+            rules: *rules,
+            guard: guard.clone(),
+        }
+    }
+
+    /// Number of distinct cells the guard stood on, the answer of part one.
+    pub fn visited(&self) -> usize {
+        self.visited
+    }
+
+    pub fn is_loop(&self) -> bool {
+        self.exit.is_none()
+    }
+
+    /// A copy of the map with the cells of the patrol marked as visited.
+    pub fn mark_visited(&self, map: &Map) -> Map {
+        let mut marked = map.clone();
+        for (position, _) in &self.path {
+            marked.map[position.y as usize][position.x as usize] = Field::Cell(true);
+        }
+        marked
+    }
+
+    /// Positions where a single new obstacle makes the guard walk in a loop, the answer of
+    /// part two. The jump table only knows the default rules, other rules try every cell.
+    pub fn loop_obstacles(&self, map: &Map) -> Vec<Position> {
+        if self.rules == Rules::default() {
+            search::find_loop_obstacles(map, self)
+        } else {
+            crate::loop_obstacles_brute_force(map, &self.guard, &self.rules)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read_file;
+
+    #[test]
+    fn test_example() {
+        let (map, guards) = read_file("input_test.txt");
+        let patrol = Patrol::run(&map, &guards[0], &Rules::default());
+
+        assert_eq!(patrol.visited(), 41);
+        assert_eq!(patrol.mark_visited(&map).get_visited_fields(), 41);
+        assert_eq!(patrol.exit, Some(Position { x: 7, y: 9 }));
+        assert_eq!(
+            patrol.turns[..3],
+            [
+                Position { x: 4, y: 1 },
+                Position { x: 8, y: 1 },
+                Position { x: 8, y: 6 }
+            ]
+        );
+        assert_eq!(patrol.path.len(), 44 + patrol.turns.len() + 1);
+        assert_eq!(patrol.loop_obstacles(&map).len(), 6);
+    }
+
+    #[test]
+    fn test_loop() {
+        let (map, guards) = crate::read_map([".#...", "....#", ".^...", "#....", "...#."]);
+        let patrol = Patrol::run(&map, &guards[0], &Rules::default());
+
+        assert!(patrol.is_loop());
+        assert_eq!(patrol.visited(), 8);
+        // the guard comes back to its initial state
+        let WalkOutcome::Loops { cycle, .. } =
+            walk::walk(&map, &Rules::default(), &guards[0], None)
+        else {
+            panic!("expected a loop");
+        };
+        assert_eq!(cycle.len(), patrol.path.len());
+    }
+}
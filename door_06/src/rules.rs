@@ -0,0 +1,175 @@
+use crate::{Direction, Field, Guard, Map, Position};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Turn {
+    #[default]
+    Right,
+    Left,
+    Around,
+}
+
+/// How the guard moves. The default is the puzzle: turn right, four directions, leave the
+/// map at its edges and walk over one-way tiles like over any other cell.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rules {
+    pub turn: Turn,
+    /// Move in eight directions. A turn is then 45 degrees instead of 90.
+    pub diagonal: bool,
+    /// Leaving the map on one side enters it again on the opposite side.
+    pub wrap: bool,
+    /// One-way tiles can only be entered moving along their arrow and block the guard
+    /// otherwise.
+    pub one_way: bool,
+}
+
+impl Rules {
+    pub fn turn(&self, direction: &mut Direction) {
+        let eighths = if self.diagonal { 1 } else { 2 };
+        *direction = match self.turn {
+            Turn::Right => direction.rotated(eighths),
+            Turn::Left => direction.rotated(8 - eighths),
+            Turn::Around => direction.rotated(4),
+        };
+    }
+
+    /// The cell in front of the guard, `None` if it would leave the map.
+    pub fn ahead(&self, map: &Map, guard: &Guard) -> Option<Position> {
+        let mut next = guard.get_next_position();
+        if self.wrap {
+            next.x = next.x.rem_euclid(map.width);
+            next.y = next.y.rem_euclid(map.height);
+        }
+        (!map.is_out_of_bounds(&next)).then_some(next)
+    }
+
+    /// Whether the guard moving in `direction` cannot enter `position`. `extra` is an
+    /// obstacle placed in addition to the ones of the map.
+    pub fn is_blocked(
+        &self,
+        map: &Map,
+        position: &Position,
+        direction: &Direction,
+        extra: Option<&Position>,
+    ) -> bool {
+        if extra == Some(position) {
+            return true;
+        }
+        match &map.map[position.y as usize][position.x as usize] {
+            Field::Obstacle => true,
+            Field::OneWay(arrow) if self.one_way => {
+                // diagonal moves may enter as long as they go along the arrow at all
+                let (ax, ay) = arrow.delta();
+                let (dx, dy) = direction.delta();
+                ax * dx + ay * dy <= 0
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patrol::Patrol;
+    use crate::{loop_obstacles_brute_force, read_file, read_map};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_default_rules() {
+        let (map, guards) = read_file("input_test.txt");
+        let rules = Rules::default();
+        let patrol = Patrol::run(&map, &guards[0], &rules);
+
+        assert_eq!(patrol.visited(), 41);
+        assert_eq!(patrol.exit, Some(Position { x: 7, y: 9 }));
+        let brute_force = loop_obstacles_brute_force(&map, &guards[0], &rules);
+        assert_eq!(brute_force.len(), 6);
+        assert_eq!(
+            brute_force.into_iter().collect::<HashSet<_>>(),
+            patrol.loop_obstacles(&map).into_iter().collect()
+        );
+    }
+
+    #[test]
+    fn test_turn_left_on_mirrored_map() {
+        let lines: Vec<String> = common::read_lines("input_test.txt")
+            .unwrap()
+            .map(|line| line.unwrap().chars().rev().collect())
+            .collect();
+        let (map, guards) = read_map(&lines);
+        let rules = Rules {
+            turn: Turn::Left,
+            ..Rules::default()
+        };
+        let patrol = Patrol::run(&map, &guards[0], &rules);
+
+        assert_eq!(patrol.visited(), 41);
+        assert_eq!(patrol.exit, Some(Position { x: 2, y: 9 }));
+        assert_eq!(patrol.loop_obstacles(&map).len(), 6);
+    }
+
+    #[test]
+    fn test_turn_around() {
+        let (map, guards) = read_map(["#", ".", "^", "."]);
+        let rules = Rules {
+            turn: Turn::Around,
+            ..Rules::default()
+        };
+        let patrol = Patrol::run(&map, &guards[0], &rules);
+
+        assert_eq!(patrol.visited(), 3);
+        assert_eq!(patrol.exit, Some(Position { x: 0, y: 3 }));
+    }
+
+    #[test]
+    fn test_diagonal() {
+        let (map, guards) = read_map([".....", "..#..", "..^.."]);
+        let diagonal = Rules {
+            diagonal: true,
+            ..Rules::default()
+        };
+        let patrol = Patrol::run(&map, &guards[0], &diagonal);
+
+        assert_eq!(patrol.visited(), 3);
+        assert_eq!(patrol.exit, Some(Position { x: 4, y: 0 }));
+        assert_eq!(
+            Patrol::run(&map, &guards[0], &Rules::default()).exit,
+            Some(Position { x: 4, y: 2 })
+        );
+    }
+
+    #[test]
+    fn test_wrap() {
+        let (map, guards) = read_map(["...", ".^.", "..."]);
+        let wrap = Rules {
+            wrap: true,
+            ..Rules::default()
+        };
+        let patrol = Patrol::run(&map, &guards[0], &wrap);
+
+        assert_eq!(patrol.visited(), 3);
+        assert!(patrol.is_loop());
+    }
+
+    #[test]
+    fn test_one_way() {
+        let one_way = Rules {
+            one_way: true,
+            ..Rules::default()
+        };
+
+        let (map, guards) = read_map([".", "↓", "^"]);
+        let patrol = Patrol::run(&map, &guards[0], &one_way);
+        assert_eq!(patrol.visited(), 1);
+        assert_eq!(patrol.exit, Some(Position { x: 0, y: 2 }));
+        let patrol = Patrol::run(&map, &guards[0], &Rules::default());
+        assert_eq!(patrol.visited(), 3);
+        assert_eq!(patrol.exit, Some(Position { x: 0, y: 0 }));
+
+        let (map, guards) = read_map([".", "↑", "^"]);
+        assert_eq!(
+            Patrol::run(&map, &guards[0], &one_way).exit,
+            Some(Position { x: 0, y: 0 })
+        );
+    }
+}
@@ -0,0 +1,264 @@
+use crate::patrol::Patrol;
+use crate::{Direction, Field, Map, Position};
+use rayon::prelude::*;
+use std::collections::HashSet;
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Right,
+    Direction::Down,
+    Direction::Left,
+];
+
+/// For every cell and direction the last cell before the next obstacle, or `None` when
+/// the guard would walk out of the map.
+struct JumpTable {
+    width: i32,
+    height: i32,
+    obstacles: Vec<bool>,
+    stops: Vec<Option<Position>>,
+}
+
+impl JumpTable {
+    fn new(map: &Map) -> Self {
+        let obstacles: Vec<bool> = map
+            .map
+            .iter()
+            .flat_map(|row| row.iter().map(|field| matches!(field, Field::Obstacle)))
+            .collect();
+        let mut table = Self {
+            width: map.width,
+            height: map.height,
+            obstacles,
+            stops: vec![None; (map.width * map.height) as usize * 4],
+        };
+
+        for direction in &DIRECTIONS {
+            let (dx, dy) = direction.delta();
+            // walk against the direction so the cell ahead is always resolved first
+            let xs: Vec<i32> = if dx > 0 {
+                (0..table.width).rev().collect()
+            } else {
+                (0..table.width).collect()
+            };
+            let ys: Vec<i32> = if dy > 0 {
+                (0..table.height).rev().collect()
+            } else {
+                (0..table.height).collect()
+            };
+
+            for y in &ys {
+                for x in &xs {
+                    let ahead = Position {
+                        x: x + dx,
+                        y: y + dy,
+                    };
+                    let stop = if map.is_out_of_bounds(&ahead) {
+                        None
+                    } else if table.is_obstacle(&ahead) {
+                        Some(Position { x: *x, y: *y })
+                    } else {
+                        table.stops[table.index(&ahead, direction)].clone()
+                    };
+                    let index = table.index(&Position { x: *x, y: *y }, direction);
+                    table.stops[index] = stop;
+                }
+            }
+        }
+
+        table
+    }
+
+    fn cell(&self, position: &Position) -> usize {
+        (position.y * self.width + position.x) as usize
+    }
+
+    fn index(&self, position: &Position, direction: &Direction) -> usize {
+        self.cell(position) * 4 + direction.index() / 2
+    }
+
+    fn is_obstacle(&self, position: &Position) -> bool {
+        self.obstacles[self.cell(position)]
+    }
+
+    /// Where the guard stops when walking straight from `position`, taking the extra
+    /// obstacle into account.
+    fn jump(
+        &self,
+        position: &Position,
+        direction: &Direction,
+        extra: &Position,
+    ) -> Option<Position> {
+        let stop = self.stops[self.index(position, direction)].clone();
+        let (dx, dy) = direction.delta();
+
+        // distance to the extra obstacle if it lies straight ahead
+        let steps = match direction {
+            Direction::Up | Direction::Down if extra.x == position.x => (extra.y - position.y) * dy,
+            Direction::Left | Direction::Right if extra.y == position.y => {
+                (extra.x - position.x) * dx
+            }
+            _ => return stop,
+        };
+        if steps <= 0 {
+            return stop;
+        }
+
+        let blocked = Position {
+            x: extra.x - dx,
+            y: extra.y - dy,
+        };
+        match &stop {
+            Some(stop) => {
+                let to_stop = (stop.x - position.x) * dx + (stop.y - position.y) * dy;
+                if steps <= to_stop {
+                    Some(blocked)
+                } else {
+                    Some(stop.clone())
+                }
+            }
+            None => Some(blocked),
+        }
+    }
+
+    /// Runs from the given state with an extra obstacle and reports whether the guard loops.
+    fn loops(
+        &self,
+        mut position: Position,
+        mut direction: Direction,
+        extra: &Position,
+        seen: &mut [u32],
+        stamp: u32,
+    ) -> bool {
+        loop {
+            position = match self.jump(&position, &direction, extra) {
+                Some(stop) => stop,
+                None => return false,
+            };
+
+            let index = self.index(&position, &direction);
+            if seen[index] == stamp {
+                return true;
+            }
+            seen[index] = stamp;
+            direction.rotate_right();
+        }
+    }
+}
+
+/// Cells the guard enters on its unobstructed path, each with the guard state right
+/// before it first steps onto the cell.
+fn path_candidates(patrol: &Patrol) -> Vec<(Position, Position, Direction)> {
+    let mut first_visit = HashSet::new();
+    first_visit.insert(&patrol.path[0].0);
+
+    patrol
+        .path
+        .windows(2)
+        .filter(|states| states[0].0 != states[1].0 && first_visit.insert(&states[1].0))
+        .map(|states| {
+            (
+                states[1].0.clone(),
+                states[0].0.clone(),
+                states[0].1.clone(),
+            )
+        })
+        .collect()
+}
+
+/// Positions where a single new obstacle makes the guard of the patrol walk in a loop.
+pub fn find_loop_obstacles(map: &Map, patrol: &Patrol) -> Vec<Position> {
+    let table = JumpTable::new(map);
+    let states = table.stops.len();
+    let candidates = path_candidates(patrol);
+
+    // an obstacle the guard never reaches cannot change its walk
+    let mut unreached = Vec::new();
+    if patrol.is_loop() {
+        let mut on_path = vec![false; table.obstacles.len()];
+        on_path[table.cell(&patrol.path[0].0)] = true;
+        candidates
+            .iter()
+            .for_each(|(candidate, _, _)| on_path[table.cell(candidate)] = true);
+
+        for y in 0..map.height {
+            for x in 0..map.width {
+                let position = Position { x, y };
+                let cell = table.cell(&position);
+                if !on_path[cell] && !table.obstacles[cell] {
+                    unreached.push(position);
+                }
+            }
+        }
+    }
+
+    candidates
+        .into_par_iter()
+        .map_init(
+            || (vec![0u32; states], 0u32),
+            |(seen, stamp), (candidate, position, direction)| {
+                *stamp += 1;
+                table
+                    .loops(position, direction, &candidate, seen, *stamp)
+                    .then_some(candidate)
+            },
+        )
+        .flatten()
+        .chain(unreached)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Rules;
+    use crate::{loop_obstacles_brute_force, read_file, read_map};
+    use common::Xorshift;
+
+    #[test]
+    fn test_example() {
+        let (map, guards) = read_file("input_test.txt");
+        let guard = guards[0].clone();
+        assert_eq!(
+            find_loop_obstacles(&map, &Patrol::run(&map, &guard, &Rules::default())).len(),
+            6
+        );
+        assert_eq!(
+            loop_obstacles_brute_force(&map, &guard, &Rules::default()).len(),
+            6
+        );
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        let mut xorshift = Xorshift::new(0x2545f491);
+        let mut random = move || xorshift.next_u32();
+
+        for _ in 0..40 {
+            let width = 6 + random() % 10;
+            let height = 6 + random() % 10;
+            let guard_x = random() % width;
+            let guard_y = random() % height;
+            let mut lines = Vec::new();
+            for y in 0..height {
+                let line: String = (0..width)
+                    .map(|x| match (x, y) {
+                        _ if x == guard_x && y == guard_y => '^',
+                        _ if random() % 6 == 0 => '#',
+                        _ => '.',
+                    })
+                    .collect();
+                lines.push(line);
+            }
+
+            let (map, guards) = read_map(lines.iter().map(String::as_str));
+            let guard = guards[0].clone();
+            assert_eq!(
+                find_loop_obstacles(&map, &Patrol::run(&map, &guard, &Rules::default())).len(),
+                loop_obstacles_brute_force(&map, &guard, &Rules::default()).len(),
+                "{}",
+                lines.join("\n")
+            );
+        }
+    }
+}
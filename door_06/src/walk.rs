@@ -0,0 +1,185 @@
+use crate::rules::Rules;
+use crate::{Direction, Guard, Map, Position};
+
+/// One bit per `(Position, Direction)` of a map.
+pub struct StateSet {
+    width: i32,
+    bits: Vec<u64>,
+}
+
+impl StateSet {
+    pub fn new(map: &Map) -> Self {
+        let states = (map.width * map.height) as usize * 8;
+        Self {
+            width: map.width,
+            bits: vec![0; states.div_ceil(64)],
+        }
+    }
+
+    fn index(&self, position: &Position, direction: &Direction) -> usize {
+        (position.y * self.width + position.x) as usize * 8 + direction.index()
+    }
+
+    /// Returns `false` if the state was already in the set.
+    pub fn insert(&mut self, position: &Position, direction: &Direction) -> bool {
+        let index = self.index(position, direction);
+        let mask = 1 << (index % 64);
+        let word = &mut self.bits[index / 64];
+        let inserted = *word & mask == 0;
+        *word |= mask;
+        inserted
+    }
+
+    pub fn contains(&self, position: &Position, direction: &Direction) -> bool {
+        let index = self.index(position, direction);
+        self.bits[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    /// Whether the position was seen in any direction.
+    pub fn contains_position(&self, position: &Position) -> bool {
+        Direction::HEADINGS
+            .iter()
+            .any(|direction| self.contains(position, direction))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum WalkOutcome {
+    /// The guard left the map from `exit` after `steps` moves from cell to cell, having
+    /// stood on `visited` distinct cells.
+    Exits {
+        steps: usize,
+        visited: usize,
+        exit: Position,
+        /// Positions where the guard turned, in order.
+        turns: Vec<Position>,
+    },
+    /// The guard repeats `cycle` forever; it starts with the first repeated state.
+    Loops {
+        cycle: Vec<(Position, Direction)>,
+        visited: usize,
+        turns: Vec<Position>,
+    },
+}
+
+impl WalkOutcome {
+    pub fn is_loop(&self) -> bool {
+        matches!(self, WalkOutcome::Loops { .. })
+    }
+}
+
+/// Walks the guard until it leaves the map or comes back to a state it was in before.
+/// `extra` is an obstacle placed in addition to the ones of the map, which is never changed.
+pub fn walk(map: &Map, rules: &Rules, guard: &Guard, extra: Option<&Position>) -> WalkOutcome {
+    trace(map, rules, guard, extra).1
+}
+
+/// Like `walk`, but also returns every state of the guard in order, starting with the
+/// initial one. A turn on the spot adds a state with the same position.
+pub fn trace(
+    map: &Map,
+    rules: &Rules,
+    guard: &Guard,
+    extra: Option<&Position>,
+) -> (Vec<(Position, Direction)>, WalkOutcome) {
+    let mut guard = guard.clone();
+    let mut states = StateSet::new(map);
+    let mut path = Vec::new();
+    let mut turns = Vec::new();
+    let mut visited = 1;
+    let mut steps = 0;
+
+    loop {
+        if !states.insert(&guard.pos, &guard.direction) {
+            let start = path
+                .iter()
+                .position(|(position, direction)| {
+                    *position == guard.pos && *direction == guard.direction
+                })
+                .unwrap();
+            let cycle = path[start..].to_vec();
+            return (
+                path,
+                WalkOutcome::Loops {
+                    cycle,
+                    visited,
+                    turns,
+                },
+            );
+        }
+        path.push((guard.pos.clone(), guard.direction.clone()));
+
+        let Some(next) = rules.ahead(map, &guard) else {
+            let exit = guard.pos;
+            return (
+                path,
+                WalkOutcome::Exits {
+                    steps,
+                    visited,
+                    exit,
+                    turns,
+                },
+            );
+        };
+
+        if rules.is_blocked(map, &next, &guard.direction, extra) {
+            turns.push(guard.pos.clone());
+            rules.turn(&mut guard.direction);
+        } else {
+            if !states.contains_position(&next) {
+                visited += 1;
+            }
+            guard.pos = next;
+            steps += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read_file;
+
+    #[test]
+    fn test_exits() {
+        let (map, guards) = read_file("input_test.txt");
+        let WalkOutcome::Exits {
+            steps,
+            visited,
+            exit,
+            turns,
+        } = walk(&map, &Rules::default(), &guards[0], None)
+        else {
+            panic!("expected the guard to exit");
+        };
+        assert_eq!((steps, visited), (44, 41));
+        assert_eq!(exit, Position { x: 7, y: 9 });
+        assert_eq!(turns.len(), 10);
+    }
+
+    #[test]
+    fn test_cycle() {
+        let (map, guards) = read_file("input_test.txt");
+        let obstacle = Position { x: 3, y: 6 };
+        let WalkOutcome::Loops { cycle, .. } =
+            walk(&map, &Rules::default(), &guards[0], Some(&obstacle))
+        else {
+            panic!("expected a loop");
+        };
+
+        // every state follows from the one before by a step or a turn, wrapping around
+        for (index, (position, direction)) in cycle.iter().enumerate() {
+            let (next_position, next_direction) = &cycle[(index + 1) % cycle.len()];
+            let rules = Rules::default();
+            let mut guard = Guard::new(position.x, position.y, direction.clone());
+            let ahead = guard.get_next_position();
+            if rules.is_blocked(&map, &ahead, &guard.direction, Some(&obstacle)) {
+                rules.turn(&mut guard.direction);
+            } else {
+                guard.pos = ahead;
+            }
+            assert!(guard.pos == *next_position && guard.direction == *next_direction);
+        }
+        assert!(cycle.contains(&(Position { x: 4, y: 6 }, Direction::Left)));
+    }
+}
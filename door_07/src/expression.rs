@@ -0,0 +1,534 @@
+use crate::number::{Failure, Number};
+use crate::operators::{Inverse, Operator};
+use crate::solver::{search, Overflow};
+use crate::Equation;
+use std::collections::HashMap;
+use std::fmt;
+
+/// How the operators between the numbers are grouped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    /// Strictly from left to right, like the puzzle.
+    LeftToRight,
+    /// Operators bind by their precedence, as in conventional arithmetic.
+    Precedence,
+    /// Any placement of parentheses.
+    Bracketed,
+}
+
+#[derive(Clone)]
+pub enum Expression<'a, N> {
+    Number(N),
+    Apply {
+        operator: &'a dyn Operator<N>,
+        left: Box<Expression<'a, N>>,
+        right: Box<Expression<'a, N>>,
+    },
+}
+
+impl<N: Number> Expression<'_, N> {
+    pub fn evaluate(&self) -> Result<N, Failure> {
+        match self {
+            Expression::Number(number) => Ok(number.clone()),
+            Expression::Apply {
+                operator,
+                left,
+                right,
+            } => operator.apply(&left.evaluate()?, &right.evaluate()?),
+        }
+    }
+}
+
+/// Every operation but the outermost one in parentheses.
+impl<N: Number> fmt::Display for Expression<'_, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operand = |expression: &Expression<N>| match expression {
+            Expression::Number(number) => number.to_string(),
+            apply => format!("({})", apply),
+        };
+        match self {
+            Expression::Number(number) => write!(f, "{}", number),
+            Expression::Apply {
+                operator,
+                left,
+                right,
+            } => write!(
+                f,
+                "{} {} {}",
+                operand(left),
+                operator.symbol(),
+                operand(right)
+            ),
+        }
+    }
+}
+
+fn apply<'a, N>(
+    operator: &'a dyn Operator<N>,
+    left: Expression<'a, N>,
+    right: Expression<'a, N>,
+) -> Expression<'a, N> {
+    Expression::Apply {
+        operator,
+        left: Box::new(left),
+        right: Box::new(right),
+    }
+}
+
+impl<N: Number> Equation<N> {
+    /// An expression of the numbers in order that evaluates to the result in `mode`.
+    pub fn expression<'a>(
+        &self,
+        operators: &[&'a dyn Operator<N>],
+        mode: Mode,
+    ) -> Result<Option<Expression<'a, N>>, Overflow> {
+        if self.numbers.is_empty() {
+            return Ok(None);
+        }
+        match mode {
+            Mode::LeftToRight => Ok(search(self, operators, 1)?.pop().map(|chosen| {
+                let mut numbers = self.numbers.iter().cloned().map(Expression::Number);
+                let first = numbers.next().unwrap();
+                chosen
+                    .iter()
+                    .zip(numbers)
+                    .fold(first, |left, (&index, right)| {
+                        apply(operators[index], left, right)
+                    })
+            })),
+            Mode::Precedence => {
+                let mut overflowed = false;
+                let mut chosen = Vec::with_capacity(self.numbers.len() - 1);
+                let found = self.with_precedence(operators, &mut chosen, &mut overflowed);
+                match found {
+                    None if overflowed => Err(Overflow),
+                    found => Ok(found),
+                }
+            }
+            Mode::Bracketed => self.bracketed(operators),
+        }
+    }
+
+    /// Tries every assignment of operators, building each expression with precedence.
+    fn with_precedence<'a>(
+        &self,
+        operators: &[&'a dyn Operator<N>],
+        chosen: &mut Vec<&'a dyn Operator<N>>,
+        overflowed: &mut bool,
+    ) -> Option<Expression<'a, N>> {
+        if chosen.len() == self.numbers.len() - 1 {
+            let expression = precedence_tree(&self.numbers, chosen);
+            return match expression.evaluate() {
+                Ok(value) if value == self.result => Some(expression),
+                Err(Failure::Overflow) => {
+                    *overflowed = true;
+                    None
+                }
+                _ => None,
+            };
+        }
+
+        for operator in operators {
+            chosen.push(*operator);
+            let found = self.with_precedence(operators, chosen, overflowed);
+            chosen.pop();
+            if found.is_some() {
+                return found;
+            }
+        }
+        None
+    }
+
+    /// Any bracketing of the numbers that evaluates to the result, see `Brackets`.
+    fn bracketed<'a>(
+        &self,
+        operators: &[&'a dyn Operator<N>],
+    ) -> Result<Option<Expression<'a, N>>, Overflow> {
+        let mut brackets = Brackets::new(&self.numbers, operators, &self.result);
+        let count = self.numbers.len();
+        if brackets.reach(0, count, &self.result) {
+            return Ok(Some(brackets.build(0, count, &self.result)));
+        }
+        if brackets.overflowed {
+            return Err(Overflow);
+        }
+        Ok(None)
+    }
+}
+
+/// How a run of numbers gets a value: the values of its first `split` numbers and of the
+/// rest, combined with `operators[operator]`.
+struct Step<N> {
+    split: usize,
+    operator: usize,
+    left: N,
+    right: N,
+}
+
+/// The values of one run of numbers, see `Brackets`.
+type Values<N> = HashMap<N, Option<Step<N>>>;
+
+/// The values runs of numbers can take under any bracketing, each with the step that gave it
+/// first, so the expression is only built for the result.
+///
+/// When no operator makes a value smaller and all of them can be undone, runs beyond the
+/// result are dropped and the result is searched from the top: the shorter side of every
+/// split is combined from its numbers and the longer side only has to reach the value that
+/// undoes the operator.
+struct Brackets<'s, 'a, N: Number> {
+    numbers: &'s [N],
+    operators: &'s [&'a dyn Operator<N>],
+    /// Values beyond it are dropped and the result is searched from the top.
+    bound: Option<&'s N>,
+    /// Every value of the run of `len` numbers from `start`, keyed by `(start, len)`.
+    values: HashMap<(usize, usize), Values<N>>,
+    /// Values the search from the top found for a run, and the ones it ruled out.
+    reached: HashMap<(usize, usize, N), Option<Step<N>>>,
+    overflowed: bool,
+}
+
+impl<'s, 'a, N: Number> Brackets<'s, 'a, N> {
+    fn new(numbers: &'s [N], operators: &'s [&'a dyn Operator<N>], result: &'s N) -> Self {
+        let bounded = numbers
+            .iter()
+            .all(|number| !number.is_negative() && !number.is_zero())
+            && operators.iter().all(|operator| {
+                operator.never_decreases()
+                    && operator.inverse(result, result).is_some()
+                    && operator.inverse_right(result, result).is_some()
+            });
+        Self {
+            numbers,
+            operators,
+            bound: bounded.then_some(result),
+            values: HashMap::new(),
+            reached: HashMap::new(),
+            overflowed: false,
+        }
+    }
+
+    /// Combines every value of every split of the run, computing the shorter runs first.
+    fn values(&mut self, start: usize, len: usize) -> &Values<N> {
+        if !self.values.contains_key(&(start, len)) {
+            let mut run = HashMap::new();
+            if len == 1 {
+                run.insert(self.numbers[start].clone(), None);
+            }
+            for split in 1..len {
+                self.values(start, split);
+                self.values(start + split, len - split);
+                let lefts = &self.values[&(start, split)];
+                let rights = &self.values[&(start + split, len - split)];
+                for left in lefts.keys() {
+                    for right in rights.keys() {
+                        for (index, operator) in self.operators.iter().enumerate() {
+                            match operator.apply(left, right) {
+                                Ok(value) if self.bound.is_some_and(|bound| value > *bound) => {}
+                                Ok(value) => {
+                                    run.entry(value).or_insert_with(|| {
+                                        Some(Step {
+                                            split,
+                                            operator: index,
+                                            left: left.clone(),
+                                            right: right.clone(),
+                                        })
+                                    });
+                                }
+                                Err(Failure::Overflow) => self.overflowed = true,
+                                Err(Failure::Undefined) => {}
+                            }
+                        }
+                    }
+                }
+            }
+            self.values.insert((start, len), run);
+        }
+        &self.values[&(start, len)]
+    }
+
+    /// Whether the run of `len` numbers from `start` can take the value `target`.
+    fn reach(&mut self, start: usize, len: usize, target: &N) -> bool {
+        let bounded = self.bound.is_some();
+        if !bounded || len <= 2 || self.values.contains_key(&(start, len)) {
+            return self.values(start, len).contains_key(target);
+        }
+        // every value of a run is at least as large as each of its numbers
+        if self.numbers[start..start + len]
+            .iter()
+            .any(|number| number > target)
+        {
+            return false;
+        }
+        let key = (start, len, target.clone());
+        if let Some(step) = self.reached.get(&key) {
+            return step.is_some();
+        }
+
+        let step = self.split(start, len, target);
+        let found = step.is_some();
+        self.reached.insert(key, step);
+        found
+    }
+
+    fn split(&mut self, start: usize, len: usize, target: &N) -> Option<Step<N>> {
+        for split in 1..len {
+            let left_is_shorter = split <= len - split;
+            let shorter: Vec<N> = if left_is_shorter {
+                self.values(start, split).keys().cloned().collect()
+            } else {
+                self.values(start + split, len - split)
+                    .keys()
+                    .cloned()
+                    .collect()
+            };
+
+            for (index, operator) in self.operators.iter().enumerate() {
+                for value in &shorter {
+                    let inverse = if left_is_shorter {
+                        operator.inverse_right(target, value)
+                    } else {
+                        operator.inverse(target, value)
+                    };
+                    let other = match inverse {
+                        Some(Inverse::Left(other)) => other,
+                        Some(Inverse::Overflow) => {
+                            self.overflowed = true;
+                            continue;
+                        }
+                        // no side is ever zero or negative
+                        Some(Inverse::Impossible | Inverse::Any | Inverse::Negative) => continue,
+                        // `new` only searches from the top with operators that can be undone
+                        None => unreachable!(),
+                    };
+                    let (left, right) = if left_is_shorter {
+                        (value.clone(), other)
+                    } else {
+                        (other, value.clone())
+                    };
+                    let reached = if left_is_shorter {
+                        self.reach(start + split, len - split, &right)
+                    } else {
+                        self.reach(start, split, &left)
+                    };
+                    if reached {
+                        return Some(Step {
+                            split,
+                            operator: index,
+                            left,
+                            right,
+                        });
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// The expression of a value `reach` found for the run of `len` numbers from `start`.
+    fn build(&self, start: usize, len: usize, value: &N) -> Expression<'a, N> {
+        let step = match self.reached.get(&(start, len, value.clone())) {
+            Some(step) => step,
+            None => &self.values[&(start, len)][value],
+        };
+        match step {
+            None => Expression::Number(value.clone()),
+            Some(step) => apply(
+                self.operators[step.operator],
+                self.build(start, step.split, &step.left),
+                self.build(start + step.split, len - step.split, &step.right),
+            ),
+        }
+    }
+}
+
+/// Groups the numbers by the precedence of the operators between them.
+fn precedence_tree<'a, N: Number>(
+    numbers: &[N],
+    operators: &[&'a dyn Operator<N>],
+) -> Expression<'a, N> {
+    let mut operands = vec![Expression::Number(numbers[0].clone())];
+    let mut pending: Vec<&'a dyn Operator<N>> = Vec::new();
+
+    let reduce = |operands: &mut Vec<Expression<'a, N>>, operator| {
+        let right = operands.pop().unwrap();
+        let left = operands.pop().unwrap();
+        operands.push(apply(operator, left, right));
+    };
+
+    for (operator, number) in operators.iter().zip(&numbers[1..]) {
+        while let Some(top) = pending.last() {
+            let binds_first = top.precedence() > operator.precedence()
+                || (top.precedence() == operator.precedence() && !operator.is_right_associative());
+            if !binds_first {
+                break;
+            }
+            reduce(&mut operands, pending.pop().unwrap());
+        }
+        pending.push(*operator);
+        operands.push(Expression::Number(number.clone()));
+    }
+    while let Some(operator) = pending.pop() {
+        reduce(&mut operands, operator);
+    }
+
+    operands.pop().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operators::{by_symbol, part_one, part_two};
+    use common::Xorshift;
+
+    fn operators(symbols: &[&str]) -> Vec<&'static dyn Operator<i128>> {
+        symbols.iter().map(|s| by_symbol(s).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_left_to_right() {
+        let expression = Equation::from("7290: 6 8 6 15")
+            .expression(&part_two(), Mode::LeftToRight)
+            .unwrap()
+            .unwrap();
+        assert_eq!(expression.to_string(), "((6 * 8) || 6) * 15");
+        assert_eq!(expression.evaluate(), Ok(7290));
+    }
+
+    #[test]
+    fn test_precedence() {
+        // 2 + 3 * 4 is 20 from left to right, but 14 with precedence
+        let equation = Equation::from("14: 2 3 4");
+        assert_eq!(
+            equation
+                .expression(&part_one(), Mode::LeftToRight)
+                .unwrap()
+                .map(|e| e.to_string()),
+            None
+        );
+        let expression = equation
+            .expression(&part_one(), Mode::Precedence)
+            .unwrap()
+            .unwrap();
+        assert_eq!(expression.to_string(), "2 + (3 * 4)");
+
+        // exponents group from the right
+        let expression = Equation::from("512: 2 3 2")
+            .expression(&operators(&["^"]), Mode::Precedence)
+            .unwrap()
+            .unwrap();
+        assert_eq!(expression.to_string(), "2 ^ (3 ^ 2)");
+    }
+
+    #[test]
+    fn test_bracketed() {
+        // 2 * (3 + 4) needs parentheses in either of the other modes
+        let equation = Equation::from("14: 2 3 4");
+        let expression = equation
+            .expression(&operators(&["*", "+"]), Mode::Bracketed)
+            .unwrap()
+            .unwrap();
+        assert_eq!(expression.evaluate(), Ok(14));
+
+        let equation = Equation::from("18: 3 2 4");
+        assert!(equation
+            .expression(&operators(&["*", "+"]), Mode::Precedence)
+            .unwrap()
+            .is_none());
+        let expression = equation
+            .expression(&operators(&["*", "+"]), Mode::Bracketed)
+            .unwrap()
+            .unwrap();
+        assert_eq!(expression.to_string(), "3 * (2 + 4)");
+    }
+
+    #[test]
+    fn test_bracketed_long_lines() {
+        // lines of the puzzle input, combining every value of every run takes minutes
+        let equation = Equation::from("8270: 2 3 74 6 75 3 1 3 3 5 3 2");
+        let expression = equation
+            .expression(&part_two(), Mode::Bracketed)
+            .unwrap()
+            .unwrap();
+        assert_eq!(expression.evaluate(), Ok(8270));
+
+        let equation = Equation::from("6151659: 7 888 16 4 2 6 1 4 4 4 7 2");
+        for operators in [part_one(), part_two()] {
+            assert!(equation
+                .expression(&operators, Mode::Bracketed)
+                .unwrap()
+                .is_none());
+        }
+    }
+
+    #[test]
+    fn test_search_from_the_top_matches_every_value() {
+        let mut xorshift = Xorshift::new(0x85ebca6b);
+        let mut random = move || xorshift.next_u32() as i128;
+
+        for _ in 0..200 {
+            let numbers: Vec<i128> = (0..2 + random() % 5).map(|_| 1 + random() % 12).collect();
+            let operators: Vec<&dyn Operator<i128>> = part_two()
+                .into_iter()
+                .filter(|_| random() % 3 != 0)
+                .collect();
+            if operators.is_empty() {
+                continue;
+            }
+            let mut every_value = Brackets::new(&numbers, &operators, &0);
+            every_value.bound = None;
+            let mut values: Vec<i128> = every_value
+                .values(0, numbers.len())
+                .keys()
+                .copied()
+                .collect();
+            values.sort();
+            // half of the results can be reached
+            let result = if random() % 2 == 0 {
+                values[random() as usize % values.len()]
+            } else {
+                random() % 3000
+            };
+            let expected = values.binary_search(&result).is_ok();
+            let found = Equation {
+                result,
+                numbers: numbers.clone(),
+            }
+            .expression(&operators, Mode::Bracketed)
+            .unwrap();
+            assert_eq!(found.is_some(), expected, "{result}: {numbers:?}");
+            if let Some(expression) = found {
+                assert_eq!(expression.evaluate(), Ok(result));
+            }
+        }
+    }
+
+    #[test]
+    fn test_modes_agree_with_solver() {
+        let lines = [
+            "190: 10 19",
+            "3267: 81 40 27",
+            "83: 17 5",
+            "292: 11 6 16 20",
+        ];
+        for line in lines {
+            let equation = Equation::from(line);
+            let solvable = equation.solve(&part_one()).unwrap();
+            let expression = equation.expression(&part_one(), Mode::LeftToRight).unwrap();
+            assert_eq!(expression.is_some(), solvable);
+            // every left-to-right expression is also a bracketing
+            if solvable {
+                assert!(equation
+                    .expression(&part_one(), Mode::Bracketed)
+                    .unwrap()
+                    .is_some());
+            }
+        }
+
+        assert_eq!(
+            Equation::from("7: 170141183460469231731687303715884105727 2")
+                .expression(&part_one(), Mode::Precedence)
+                .map(|e| e.is_some()),
+            Err(Overflow)
+        );
+    }
+}
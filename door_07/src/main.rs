@@ -1,78 +1,188 @@
-use common::read_lines;
-use std::ops::{AddAssign, BitAnd};
+mod expression;
+mod number;
+mod operators;
+mod solver;
+mod witness;
+
+use common::{read_lines, MixedRadix};
+use expression::Mode;
+use num_bigint::BigInt;
+use number::Number;
+use operators::Operator;
+use std::ops::BitAnd;
 use std::str::FromStr;
 
 fn main() {
     let path = "door_07/input.txt";
-    let number_of_true: i128 = read_lines(path)
-        .unwrap()
-        .map(|l| Equation::from(l.unwrap().as_str()))
-        .filter_map(|e| {
-            if e.has_solution_2() {
-                return Some(e.result);
-            }
-            None
-        })
-        .sum();
-    print!("Number of true solutions: {}", number_of_true);
-}
+    let lines: Vec<String> = read_lines(path).unwrap().map(|l| l.unwrap()).collect();
 
-pub struct State {
-    num_of_states: usize,
-    state: Vec<u32>,
+    // --reference enumerates every operator combination instead of solving backwards
+    if std::env::args().any(|arg| arg == "--reference") {
+        let equations: Vec<Equation> = lines.iter().map(|l| Equation::from(l.as_str())).collect();
+        let calibration = |solvable: fn(&Equation) -> bool| -> i128 {
+            equations
+                .iter()
+                .filter(|e| solvable(e))
+                .map(|e| e.result)
+                .sum()
+        };
+        println!(
+            "Number of true solutions without concatenation: {}",
+            calibration(Equation::has_solution)
+        );
+        print!(
+            "Number of true solutions: {}",
+            calibration(Equation::has_solution_2)
+        );
+        return;
+    }
+
+    // --number=u64|i128|big picks the arithmetic, i128 by default
+    match std::env::args()
+        .find_map(|arg| arg.strip_prefix("--number=").map(String::from))
+        .as_deref()
+    {
+        None | Some("i128") => run::<i128>(&lines),
+        Some("u64") => run::<u64>(&lines),
+        Some("big") => run::<BigInt>(&lines),
+        Some(other) => panic!("unknown number type {}", other),
+    }
 }
 
-impl State {
-    pub fn new(size: usize, num_of_states: usize) -> Self {
-        Self {
-            num_of_states,
-            state: vec![0; size],
+fn run<N: Number>(lines: &[String]) {
+    let equations: Vec<Equation<N>> = lines
+        .iter()
+        .map(|l| Equation::parse(l).expect("equation does not fit the number type"))
+        .collect();
+
+    // --operators=+,*,xor sums the equations solvable with the given operators instead
+    let symbols =
+        std::env::args().find_map(|arg| arg.strip_prefix("--operators=").map(String::from));
+    let operators: Vec<&dyn Operator<N>> = match &symbols {
+        Some(symbols) => symbols
+            .split(',')
+            .map(|symbol| operators::by_symbol(symbol).expect("unknown operator"))
+            .collect(),
+        None => operators::part_two(),
+    };
+
+    // --mode=precedence|brackets groups the operators differently than left to right
+    let mode = match std::env::args()
+        .find_map(|arg| arg.strip_prefix("--mode=").map(String::from))
+        .as_deref()
+    {
+        None | Some("ltr") => Mode::LeftToRight,
+        Some("precedence") => Mode::Precedence,
+        Some("brackets") => Mode::Bracketed,
+        Some(other) => panic!("unknown mode {}", other),
+    };
+
+    // --witnesses lists one solution per equation, --witnesses=all every one of them
+    if let Some(all) = std::env::args().find_map(|arg| match arg.as_str() {
+        "--witnesses" => Some(false),
+        "--witnesses=all" => Some(true),
+        _ => None,
+    }) {
+        if mode == Mode::LeftToRight {
+            print_witnesses(&equations, &operators, all);
+        } else {
+            print_expressions(&equations, &operators, mode);
         }
+        return;
     }
 
-    pub fn state(&self) -> &[u32] {
-        self.state.as_slice()
+    let calibration = |operators: &[&dyn Operator<N>]| -> N {
+        equations
+            .iter()
+            .filter(|e| {
+                let solvable = match mode {
+                    Mode::LeftToRight => e.solve(operators),
+                    mode => e.expression(operators, mode).map(|e| e.is_some()),
+                };
+                solvable.unwrap_or_else(|_| panic!("{} overflows, try --number=big", e.result))
+            })
+            .try_fold(N::from_u32(0), |sum, e| sum.add(&e.result))
+            .expect("sum overflows, try --number=big")
+    };
+
+    if let Some(symbols) = symbols {
+        println!(
+            "Number of true solutions with {}: {}",
+            symbols,
+            calibration(&operators)
+        );
+        return;
     }
 
-    pub fn next(&mut self) {
-        let mut overflow = false;
-        for (index, state) in self.state.iter_mut().enumerate() {
-            if index == 0 {
-                state.add_assign(1);
-                if *state == self.num_of_states as u32 {
-                    overflow = true;
-                    *state = 0;
-                }
-            } else {
-                if overflow {
-                    overflow = false;
-                    state.add_assign(1);
-                    if *state == self.num_of_states as u32 {
-                        overflow = true;
-                        *state = 0;
-                    }
-                }
-            }
+    println!(
+        "Number of true solutions without concatenation: {}",
+        calibration(&operators::part_one())
+    );
+    print!("Number of true solutions: {}", calibration(&operators));
+}
+
+fn print_witnesses<N: Number>(
+    equations: &[Equation<N>],
+    operators: &[&dyn Operator<N>],
+    all: bool,
+) {
+    for equation in equations {
+        let numbers: Vec<String> = equation.numbers.iter().map(N::to_string).collect();
+        let count = match equation.count_solutions(operators) {
+            Ok(1) => String::from("1 solution"),
+            Ok(count) => format!("{} solutions", count),
+            Err(_) => String::from("overflow"),
+        };
+        println!("{}: {} -- {}", equation.result, numbers.join(" "), count);
+
+        let witnesses = if all {
+            equation.witnesses(operators)
+        } else {
+            equation.witness(operators).map(Vec::from_iter)
+        };
+        for witness in witnesses.unwrap_or_default() {
+            println!("  {}", witness);
         }
     }
 }
 
-pub struct Equation {
-    pub result: i128,
-    pub numbers: Vec<i128>,
+fn print_expressions<N: Number>(
+    equations: &[Equation<N>],
+    operators: &[&dyn Operator<N>],
+    mode: Mode,
+) {
+    for equation in equations {
+        let numbers: Vec<String> = equation.numbers.iter().map(N::to_string).collect();
+        println!("{}: {}", equation.result, numbers.join(" "));
+        match equation.expression(operators, mode) {
+            Ok(Some(expression)) => println!("  {} = {}", expression, equation.result),
+            Ok(None) => {}
+            Err(_) => println!("  overflow"),
+        }
+    }
+}
+
+pub struct Equation<N = i128> {
+    pub result: N,
+    pub numbers: Vec<N>,
 }
 
 impl From<&str> for Equation {
     fn from(value: &str) -> Self {
-        let a = value.find(":").unwrap();
-        let result = value[..a].parse::<i128>().unwrap();
-        let values = &value[a + 1..];
+        Self::parse(value).unwrap()
+    }
+}
+
+impl<N: Number> Equation<N> {
+    /// `None` when the line is malformed or a number does not fit into `N`.
+    pub fn parse(value: &str) -> Option<Self> {
+        let (result, values) = value.split_once(':')?;
+        let result = result.trim().parse::<N>().ok()?;
         let numbers = values
-            .trim()
-            .split(" ")
-            .map(|x| x.parse::<i128>().unwrap())
-            .collect::<Vec<i128>>();
-        Self { result, numbers }
+            .split_whitespace()
+            .map(|x| x.parse::<N>().ok())
+            .collect::<Option<Vec<N>>>()?;
+        Some(Self { result, numbers })
     }
 }
 
@@ -105,11 +215,8 @@ impl Equation {
     }
 
     pub fn has_solution_2(&self) -> bool {
-        let mut state_machine = State::new(self.numbers.len() - 1, 3);
         let size = self.numbers.len() - 1;
-        let size_of_solution_room = 3_u32.pow(size as u32);
-        for i in 0..size_of_solution_room {
-            let state = state_machine.state();
+        for state in &MixedRadix::uniform(size, 3) {
             let mut res = self.numbers[0];
             let mut index = 1;
             for operation in state {
@@ -130,8 +237,6 @@ impl Equation {
             if res == self.result {
                 return true;
             }
-
-            state_machine.next();
         }
         false
     }
@@ -221,59 +326,44 @@ mod tests {
 
     #[test]
     fn test_state() {
-        let mut state = State::new(2, 2);
-        assert_eq!(state.state(), &[0, 0]);
-        state.next();
-        assert_eq!(state.state(), &[1, 0]);
-        state.next();
-        assert_eq!(state.state(), &[0, 1]);
-        state.next();
-        assert_eq!(state.state(), &[1, 1]);
-        state.next();
-        assert_eq!(state.state(), &[0, 0]);
+        let states: Vec<Vec<u32>> = MixedRadix::uniform(2, 2).iter().collect();
+        assert_eq!(states, [[0, 0], [1, 0], [0, 1], [1, 1]]);
     }
 
     #[test]
     fn test_state_with_size_of_three() {
-        let mut state = State::new(3, 2);
-        assert_eq!(state.state(), &[0, 0, 0]);
-        state.next();
-        assert_eq!(state.state(), &[1, 0, 0]);
-        state.next();
-        assert_eq!(state.state(), &[0, 1, 0]);
-        state.next();
-        assert_eq!(state.state(), &[1, 1, 0]);
-        state.next();
-        assert_eq!(state.state(), &[0, 0, 1]);
-        state.next();
-        assert_eq!(state.state(), &[1, 0, 1]);
-        state.next();
-        assert_eq!(state.state(), &[0, 1, 1]);
-        state.next();
-        assert_eq!(state.state(), &[1, 1, 1]);
+        let states: Vec<Vec<u32>> = MixedRadix::uniform(3, 2).iter().collect();
+        assert_eq!(
+            states,
+            [
+                [0, 0, 0],
+                [1, 0, 0],
+                [0, 1, 0],
+                [1, 1, 0],
+                [0, 0, 1],
+                [1, 0, 1],
+                [0, 1, 1],
+                [1, 1, 1]
+            ]
+        );
     }
 
     #[test]
     fn test_state_with_three_pos_states() {
-        let mut state = State::new(2, 3);
-        assert_eq!(state.state(), &[0, 0]);
-        state.next();
-        assert_eq!(state.state(), &[1, 0]);
-        state.next();
-        assert_eq!(state.state(), &[2, 0]);
-        state.next();
-        assert_eq!(state.state(), &[0, 1]);
-        state.next();
-        assert_eq!(state.state(), &[1, 1]);
-        state.next();
-        assert_eq!(state.state(), &[2, 1]);
-        state.next();
-        assert_eq!(state.state(), &[0, 2]);
-        state.next();
-        assert_eq!(state.state(), &[1, 2]);
-        state.next();
-        assert_eq!(state.state(), &[2, 2]);
-        state.next();
-        assert_eq!(state.state(), &[0, 0]);
+        let states: Vec<Vec<u32>> = MixedRadix::uniform(2, 3).iter().collect();
+        assert_eq!(
+            states,
+            [
+                [0, 0],
+                [1, 0],
+                [2, 0],
+                [0, 1],
+                [1, 1],
+                [2, 1],
+                [0, 2],
+                [1, 2],
+                [2, 2]
+            ]
+        );
     }
 }
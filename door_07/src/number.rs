@@ -0,0 +1,218 @@
+use num_bigint::{BigInt, Sign};
+use std::fmt;
+use std::hash::Hash;
+use std::str::FromStr;
+
+/// Why an arithmetic operation gave no value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Failure {
+    /// The operation is not defined for the operands, like a division by zero.
+    Undefined,
+    /// The value does not fit into the number type.
+    Overflow,
+}
+
+/// The number type of equations. Every operation is checked, so a value that does not fit
+/// is reported as `Failure::Overflow` instead of wrapping around.
+pub trait Number:
+    Clone + Eq + Hash + PartialOrd + fmt::Debug + fmt::Display + FromStr + Send + Sync + 'static
+{
+    fn from_u32(value: u32) -> Self;
+    fn add(&self, other: &Self) -> Result<Self, Failure>;
+    fn sub(&self, other: &Self) -> Result<Self, Failure>;
+    fn mul(&self, other: &Self) -> Result<Self, Failure>;
+    /// Rounds towards zero.
+    fn div(&self, other: &Self) -> Result<Self, Failure>;
+    fn rem(&self, other: &Self) -> Result<Self, Failure>;
+    fn power(&self, exponent: u32) -> Result<Self, Failure>;
+    fn xor(&self, other: &Self) -> Self;
+    /// `None` for negative numbers and numbers beyond `u32::MAX`.
+    fn to_u32(&self) -> Option<u32>;
+
+    fn is_zero(&self) -> bool {
+        *self == Self::from_u32(0)
+    }
+
+    fn is_negative(&self) -> bool {
+        *self < Self::from_u32(0)
+    }
+
+    /// 10 to the power of the number of digits of a non-negative number.
+    fn shift(&self) -> Result<Self, Failure> {
+        let ten = Self::from_u32(10);
+        let mut shift = ten.clone();
+        while shift <= *self {
+            shift = shift.mul(&ten)?;
+        }
+        Ok(shift)
+    }
+}
+
+fn checked<N>(value: Option<N>) -> Result<N, Failure> {
+    value.ok_or(Failure::Overflow)
+}
+
+fn divisor<N: Number>(other: &N) -> Result<(), Failure> {
+    if other.is_zero() {
+        Err(Failure::Undefined)
+    } else {
+        Ok(())
+    }
+}
+
+/// Subtracting below zero is an overflow like going beyond `u64::MAX`, a wider type may
+/// still find the answer.
+impl Number for u64 {
+    fn from_u32(value: u32) -> Self {
+        value.into()
+    }
+
+    fn add(&self, other: &Self) -> Result<Self, Failure> {
+        checked(self.checked_add(*other))
+    }
+
+    fn sub(&self, other: &Self) -> Result<Self, Failure> {
+        checked(self.checked_sub(*other))
+    }
+
+    fn mul(&self, other: &Self) -> Result<Self, Failure> {
+        checked(self.checked_mul(*other))
+    }
+
+    fn div(&self, other: &Self) -> Result<Self, Failure> {
+        divisor(other)?;
+        Ok(self / other)
+    }
+
+    fn rem(&self, other: &Self) -> Result<Self, Failure> {
+        divisor(other)?;
+        Ok(self % other)
+    }
+
+    fn power(&self, exponent: u32) -> Result<Self, Failure> {
+        checked(self.checked_pow(exponent))
+    }
+
+    fn xor(&self, other: &Self) -> Self {
+        self ^ other
+    }
+
+    fn to_u32(&self) -> Option<u32> {
+        u32::try_from(*self).ok()
+    }
+}
+
+impl Number for i128 {
+    fn from_u32(value: u32) -> Self {
+        value.into()
+    }
+
+    fn add(&self, other: &Self) -> Result<Self, Failure> {
+        checked(self.checked_add(*other))
+    }
+
+    fn sub(&self, other: &Self) -> Result<Self, Failure> {
+        checked(self.checked_sub(*other))
+    }
+
+    fn mul(&self, other: &Self) -> Result<Self, Failure> {
+        checked(self.checked_mul(*other))
+    }
+
+    fn div(&self, other: &Self) -> Result<Self, Failure> {
+        divisor(other)?;
+        checked(self.checked_div(*other))
+    }
+
+    fn rem(&self, other: &Self) -> Result<Self, Failure> {
+        divisor(other)?;
+        checked(self.checked_rem(*other))
+    }
+
+    fn power(&self, exponent: u32) -> Result<Self, Failure> {
+        checked(self.checked_pow(exponent))
+    }
+
+    fn xor(&self, other: &Self) -> Self {
+        self ^ other
+    }
+
+    fn to_u32(&self) -> Option<u32> {
+        u32::try_from(*self).ok()
+    }
+}
+
+/// Powers give up beyond this many bits, so a large exponent cannot exhaust the memory.
+const MAX_POW_BITS: u64 = 1 << 20;
+
+impl Number for BigInt {
+    fn from_u32(value: u32) -> Self {
+        value.into()
+    }
+
+    fn add(&self, other: &Self) -> Result<Self, Failure> {
+        Ok(self + other)
+    }
+
+    fn sub(&self, other: &Self) -> Result<Self, Failure> {
+        Ok(self - other)
+    }
+
+    fn mul(&self, other: &Self) -> Result<Self, Failure> {
+        Ok(self * other)
+    }
+
+    fn div(&self, other: &Self) -> Result<Self, Failure> {
+        divisor(other)?;
+        Ok(self / other)
+    }
+
+    fn rem(&self, other: &Self) -> Result<Self, Failure> {
+        divisor(other)?;
+        Ok(self % other)
+    }
+
+    fn power(&self, exponent: u32) -> Result<Self, Failure> {
+        if self.bits().saturating_mul(exponent.into()) > MAX_POW_BITS {
+            return Err(Failure::Overflow);
+        }
+        Ok(self.pow(exponent))
+    }
+
+    fn xor(&self, other: &Self) -> Self {
+        self ^ other
+    }
+
+    fn to_u32(&self) -> Option<u32> {
+        u32::try_from(self).ok()
+    }
+
+    fn is_negative(&self) -> bool {
+        self.sign() == Sign::Minus
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overflow() {
+        assert_eq!(u64::MAX.add(&1), Err(Failure::Overflow));
+        assert_eq!(0u64.sub(&1), Err(Failure::Overflow));
+        assert_eq!(i128::MAX.mul(&2), Err(Failure::Overflow));
+        assert_eq!(i128::MIN.div(&-1), Err(Failure::Overflow));
+        assert_eq!(7i128.rem(&0), Err(Failure::Undefined));
+        assert_eq!(10u64.power(20), Err(Failure::Overflow));
+        assert_eq!(BigInt::from(2).power(u32::MAX), Err(Failure::Overflow));
+    }
+
+    #[test]
+    fn test_shift() {
+        assert_eq!(0u64.shift(), Ok(10));
+        assert_eq!(99i128.shift(), Ok(100));
+        assert_eq!(100i128.shift(), Ok(1000));
+        assert_eq!(u64::MAX.shift(), Err(Failure::Overflow));
+        assert_eq!(BigInt::from(u64::MAX).shift(), Ok(BigInt::from(10).pow(20)));
+    }
+}
@@ -0,0 +1,465 @@
+use crate::number::{Failure, Number};
+
+/// What the left operand of an operator must have been to give a result.
+#[derive(Debug, PartialEq)]
+pub enum Inverse<N> {
+    /// No left operand gives the result.
+    Impossible,
+    Left(N),
+    /// Every left operand gives the result, like when multiplying by zero.
+    Any,
+    /// The left operand does not fit into the number type.
+    Overflow,
+    /// The left operand is below zero and does not fit into the number type, which only
+    /// matters when partial results can be negative.
+    Negative,
+}
+
+/// A binary operator that can be inserted between two numbers of an equation.
+pub trait Operator<N: Number>: Sync {
+    fn symbol(&self) -> &'static str;
+
+    fn apply(&self, left: &N, right: &N) -> Result<N, Failure>;
+
+    /// Undoes the operator for the backward solver. Operators returning `None` make the
+    /// solver try every combination forwards instead.
+    fn inverse(&self, _result: &N, _right: &N) -> Option<Inverse<N>> {
+        None
+    }
+
+    /// What the right operand must have been to give a result with the given left one, held
+    /// in `Inverse::Left`. Operators returning `None` make the bracketed search combine every
+    /// value of both sides instead.
+    fn inverse_right(&self, _result: &N, _left: &N) -> Option<Inverse<N>> {
+        None
+    }
+
+    /// Operators with a higher precedence bind tighter when evaluating with precedence. The
+    /// built-in ones bind `^` tightest, then `*` and `/`, `+` and `-`, `||` and `xor`.
+    fn precedence(&self) -> u8;
+
+    fn is_right_associative(&self) -> bool {
+        false
+    }
+
+    /// Whether non-negative operands always give a non-negative result. The backward solver
+    /// then drops every branch that needs a negative left operand.
+    fn keeps_non_negative(&self) -> bool {
+        false
+    }
+
+    /// Whether positive operands always give a result at least as large as either of them.
+    /// The bracketed search then drops every value beyond the result.
+    fn never_decreases(&self) -> bool {
+        false
+    }
+}
+
+pub struct Add;
+pub struct Multiply;
+/// Appends the digits of the right operand to the left one, only for non-negative numbers.
+pub struct Concatenate;
+pub struct Subtract;
+/// Division rounding towards zero.
+pub struct Divide;
+pub struct Exponent;
+pub struct Xor;
+
+/// The operators of part one.
+pub fn part_one<N: Number>() -> Vec<&'static dyn Operator<N>> {
+    vec![&Add, &Multiply]
+}
+
+/// The operators of part two.
+pub fn part_two<N: Number>() -> Vec<&'static dyn Operator<N>> {
+    vec![&Add, &Multiply, &Concatenate]
+}
+
+pub fn all<N: Number>() -> Vec<&'static dyn Operator<N>> {
+    vec![
+        &Add,
+        &Multiply,
+        &Concatenate,
+        &Subtract,
+        &Divide,
+        &Exponent,
+        &Xor,
+    ]
+}
+
+pub fn by_symbol<N: Number>(symbol: &str) -> Option<&'static dyn Operator<N>> {
+    all()
+        .into_iter()
+        .find(|operator| operator.symbol() == symbol)
+}
+
+fn exact<N>(left: Result<N, Failure>) -> Option<Inverse<N>> {
+    Some(match left {
+        Ok(left) => Inverse::Left(left),
+        Err(Failure::Undefined) => Inverse::Impossible,
+        Err(Failure::Overflow) => Inverse::Overflow,
+    })
+}
+
+impl<N: Number> Operator<N> for Add {
+    fn never_decreases(&self) -> bool {
+        true
+    }
+
+    fn keeps_non_negative(&self) -> bool {
+        true
+    }
+
+    fn symbol(&self) -> &'static str {
+        "+"
+    }
+
+    fn precedence(&self) -> u8 {
+        2
+    }
+
+    fn apply(&self, left: &N, right: &N) -> Result<N, Failure> {
+        left.add(right)
+    }
+
+    fn inverse(&self, result: &N, right: &N) -> Option<Inverse<N>> {
+        match result.sub(right) {
+            Err(Failure::Overflow) if result < right => Some(Inverse::Negative),
+            left => exact(left),
+        }
+    }
+
+    fn inverse_right(&self, result: &N, left: &N) -> Option<Inverse<N>> {
+        self.inverse(result, left)
+    }
+}
+
+impl<N: Number> Operator<N> for Multiply {
+    fn never_decreases(&self) -> bool {
+        true
+    }
+
+    fn keeps_non_negative(&self) -> bool {
+        true
+    }
+
+    fn symbol(&self) -> &'static str {
+        "*"
+    }
+
+    fn precedence(&self) -> u8 {
+        3
+    }
+
+    fn apply(&self, left: &N, right: &N) -> Result<N, Failure> {
+        left.mul(right)
+    }
+
+    fn inverse(&self, result: &N, right: &N) -> Option<Inverse<N>> {
+        if right.is_zero() {
+            return Some(if result.is_zero() {
+                Inverse::Any
+            } else {
+                Inverse::Impossible
+            });
+        }
+        match result.rem(right) {
+            Ok(rest) if rest.is_zero() => exact(result.div(right)),
+            Ok(_) => Some(Inverse::Impossible),
+            Err(failure) => exact(Err(failure)),
+        }
+    }
+
+    fn inverse_right(&self, result: &N, left: &N) -> Option<Inverse<N>> {
+        self.inverse(result, left)
+    }
+}
+
+impl<N: Number> Operator<N> for Concatenate {
+    fn never_decreases(&self) -> bool {
+        true
+    }
+
+    fn keeps_non_negative(&self) -> bool {
+        true
+    }
+
+    fn symbol(&self) -> &'static str {
+        "||"
+    }
+
+    fn precedence(&self) -> u8 {
+        1
+    }
+
+    fn apply(&self, left: &N, right: &N) -> Result<N, Failure> {
+        if left.is_negative() || right.is_negative() {
+            return Err(Failure::Undefined);
+        }
+        if left.is_zero() {
+            return Ok(right.clone());
+        }
+        left.mul(&right.shift()?)?.add(right)
+    }
+
+    fn inverse(&self, result: &N, right: &N) -> Option<Inverse<N>> {
+        if right.is_negative() || result.is_negative() {
+            return Some(Inverse::Impossible);
+        }
+        let shift = match right.shift() {
+            Ok(shift) => shift,
+            // only a zero on the left keeps the result in range
+            Err(_) if result == right => return Some(Inverse::Left(N::from_u32(0))),
+            Err(_) => return Some(Inverse::Impossible),
+        };
+        match result.rem(&shift) {
+            Ok(suffix) if suffix == *right => exact(result.div(&shift)),
+            Ok(_) => Some(Inverse::Impossible),
+            Err(failure) => exact(Err(failure)),
+        }
+    }
+
+    fn inverse_right(&self, result: &N, left: &N) -> Option<Inverse<N>> {
+        if left.is_negative() || result.is_negative() {
+            return Some(Inverse::Impossible);
+        }
+        if left.is_zero() {
+            return Some(Inverse::Left(result.clone()));
+        }
+        // the right operand has as many digits as the result has more than the left one
+        let shift = match (result.shift(), left.shift()) {
+            (Ok(result), Ok(left)) if result > left => result.div(&left),
+            (Ok(_), Ok(_)) => return Some(Inverse::Impossible),
+            (Err(failure), _) | (_, Err(failure)) => Err(failure),
+        };
+        let shift = match shift {
+            Ok(shift) => shift,
+            Err(failure) => return exact(Err(failure)),
+        };
+        match (result.div(&shift), result.rem(&shift)) {
+            (Ok(prefix), Ok(right)) if prefix == *left && right.shift() == Ok(shift.clone()) => {
+                Some(Inverse::Left(right))
+            }
+            (Ok(_), Ok(_)) => Some(Inverse::Impossible),
+            (Err(failure), _) | (_, Err(failure)) => exact(Err(failure)),
+        }
+    }
+}
+
+impl<N: Number> Operator<N> for Subtract {
+    fn symbol(&self) -> &'static str {
+        "-"
+    }
+
+    fn precedence(&self) -> u8 {
+        2
+    }
+
+    fn apply(&self, left: &N, right: &N) -> Result<N, Failure> {
+        left.sub(right)
+    }
+
+    fn inverse(&self, result: &N, right: &N) -> Option<Inverse<N>> {
+        exact(result.add(right))
+    }
+
+    fn inverse_right(&self, result: &N, left: &N) -> Option<Inverse<N>> {
+        exact(left.sub(result))
+    }
+}
+
+impl<N: Number> Operator<N> for Divide {
+    fn keeps_non_negative(&self) -> bool {
+        true
+    }
+
+    fn symbol(&self) -> &'static str {
+        "/"
+    }
+
+    fn precedence(&self) -> u8 {
+        3
+    }
+
+    fn apply(&self, left: &N, right: &N) -> Result<N, Failure> {
+        left.div(right)
+    }
+}
+
+impl<N: Number> Operator<N> for Exponent {
+    fn keeps_non_negative(&self) -> bool {
+        true
+    }
+
+    fn symbol(&self) -> &'static str {
+        "^"
+    }
+
+    fn precedence(&self) -> u8 {
+        4
+    }
+
+    fn is_right_associative(&self) -> bool {
+        true
+    }
+
+    fn apply(&self, left: &N, right: &N) -> Result<N, Failure> {
+        if right.is_negative() {
+            return Err(Failure::Undefined);
+        }
+        left.power(right.to_u32().ok_or(Failure::Overflow)?)
+    }
+}
+
+impl<N: Number> Operator<N> for Xor {
+    fn keeps_non_negative(&self) -> bool {
+        true
+    }
+
+    fn symbol(&self) -> &'static str {
+        "xor"
+    }
+
+    fn precedence(&self) -> u8 {
+        0
+    }
+
+    fn apply(&self, left: &N, right: &N) -> Result<N, Failure> {
+        Ok(left.xor(right))
+    }
+
+    fn inverse(&self, result: &N, right: &N) -> Option<Inverse<N>> {
+        Some(Inverse::Left(result.xor(right)))
+    }
+
+    fn inverse_right(&self, result: &N, left: &N) -> Option<Inverse<N>> {
+        self.inverse(result, left)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let cases = [
+            ("+", 6, 8, Some(14)),
+            ("*", 6, 8, Some(48)),
+            ("||", 48, 6, Some(486)),
+            ("||", 0, 6, Some(6)),
+            ("||", -1, 6, None),
+            ("||", 1, 1, Some(11)),
+            ("-", 6, 8, Some(-2)),
+            ("/", 7, 2, Some(3)),
+            ("/", 7, 0, None),
+            ("^", 2, 10, Some(1024)),
+            ("^", 2, -1, None),
+            ("^", 10, 40, None),
+            ("xor", 5, 3, Some(6)),
+        ];
+        for (symbol, left, right, expected) in cases {
+            assert_eq!(
+                by_symbol::<i128>(symbol).unwrap().apply(&left, &right).ok(),
+                expected,
+                "{left} {symbol} {right}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_inverse_undoes_apply() {
+        for operator in all::<i128>() {
+            for left in -30..30 {
+                for right in -30..30 {
+                    let Ok(result) = operator.apply(&left, &right) else {
+                        continue;
+                    };
+                    match operator.inverse(&result, &right) {
+                        None | Some(Inverse::Any) => {}
+                        Some(inverse) => assert_eq!(
+                            inverse,
+                            Inverse::Left(left),
+                            "{left} {} {right}",
+                            operator.symbol()
+                        ),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_inverse_is_exact() {
+        for operator in all::<i128>() {
+            for result in -100..100 {
+                for right in -30..30 {
+                    let left = match operator.inverse(&result, &right) {
+                        Some(Inverse::Left(left)) => left,
+                        Some(Inverse::Any) => 0,
+                        _ => continue,
+                    };
+                    assert_eq!(
+                        operator.apply(&left, &right),
+                        Ok(result),
+                        "{left} {} {right}",
+                        operator.symbol()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_inverse_right_undoes_apply() {
+        for operator in all::<i128>() {
+            for left in -30..30 {
+                for right in -30..30 {
+                    let Ok(result) = operator.apply(&left, &right) else {
+                        continue;
+                    };
+                    match operator.inverse_right(&result, &left) {
+                        None | Some(Inverse::Any) => {}
+                        Some(inverse) => assert_eq!(
+                            inverse,
+                            Inverse::Left(right),
+                            "{left} {} {right}",
+                            operator.symbol()
+                        ),
+                    }
+                }
+            }
+        }
+        // 12 || 3 is 123, not 1203
+        assert_eq!(
+            Concatenate.inverse_right(&1203i128, &12),
+            Some(Inverse::Impossible)
+        );
+        assert_eq!(
+            Concatenate.inverse_right(&120i128, &12),
+            Some(Inverse::Left(0))
+        );
+        assert_eq!(
+            Concatenate.inverse_right(&1200i128, &12),
+            Some(Inverse::Impossible)
+        );
+    }
+
+    #[test]
+    fn test_inverse_overflow() {
+        assert_eq!(Subtract.inverse(&u64::MAX, &1), Some(Inverse::Overflow));
+        assert_eq!(Add.inverse(&1u64, &2), Some(Inverse::Negative));
+        assert_eq!(
+            Operator::<u64>::apply(&Subtract, &1, &2),
+            Err(Failure::Overflow)
+        );
+        assert_eq!(
+            Concatenate.inverse(&u64::MAX, &u64::MAX),
+            Some(Inverse::Left(0))
+        );
+        assert_eq!(
+            Operator::<u64>::apply(&Concatenate, &1, &u64::MAX),
+            Err(Failure::Overflow)
+        );
+    }
+}
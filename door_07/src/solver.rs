@@ -0,0 +1,379 @@
+use crate::number::{Failure, Number};
+use crate::operators::{part_one, part_two, Inverse, Operator};
+use crate::Equation;
+
+/// Some combination of operators gave a value that does not fit into the number type, so
+/// the equation may have a solution that only a wider type can find.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Overflow;
+
+impl<N: Number> Equation<N> {
+    /// Whether the numbers can be combined into the result with `+`, `*` and, when
+    /// `concatenation` is set, `||`.
+    pub fn is_solvable(&self, concatenation: bool) -> Result<bool, Overflow> {
+        let operators = if concatenation {
+            part_two()
+        } else {
+            part_one()
+        };
+        self.solve(&operators)
+    }
+
+    /// Whether the numbers, evaluated from left to right, can be combined into the result
+    /// with the given operators.
+    pub fn solve(&self, operators: &[&dyn Operator<N>]) -> Result<bool, Overflow> {
+        Ok(!search(self, operators, 1)?.is_empty())
+    }
+}
+
+/// Up to `limit` assignments of operators that make the equation hold, each as the indices
+/// into `operators` from left to right. Works backwards from the result while every
+/// operator can be undone: the last operator must undo exactly into the result, which rules
+/// out most branches right away.
+///
+/// Fails when fewer than `limit` assignments were found and some combination overflowed.
+pub fn search<N: Number>(
+    equation: &Equation<N>,
+    operators: &[&dyn Operator<N>],
+    limit: usize,
+) -> Result<Vec<Vec<usize>>, Overflow> {
+    if equation.numbers.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut search = Search::new(operators, &equation.numbers, limit);
+    if search
+        .backward(&equation.result, equation.numbers.len())
+        .is_none()
+    {
+        search = Search::new(operators, &equation.numbers, limit);
+        search.forward(
+            equation.numbers[0].clone(),
+            1,
+            equation.numbers.len(),
+            Some(&equation.result),
+        );
+    }
+
+    if search.overflowed && !search.is_full() {
+        return Err(Overflow);
+    }
+    Ok(search.found)
+}
+
+struct Search<'a, N: Number> {
+    operators: &'a [&'a dyn Operator<N>],
+    numbers: &'a [N],
+    limit: usize,
+    /// The operator between `numbers[i]` and `numbers[i + 1]` at index `i`.
+    chosen: Vec<usize>,
+    found: Vec<Vec<usize>>,
+    overflowed: bool,
+    /// No partial result can be negative, see `Operator::keeps_non_negative`.
+    non_negative: bool,
+}
+
+impl<'a, N: Number> Search<'a, N> {
+    fn new(operators: &'a [&'a dyn Operator<N>], numbers: &'a [N], limit: usize) -> Self {
+        Self {
+            operators,
+            numbers,
+            limit,
+            chosen: vec![0; numbers.len() - 1],
+            found: Vec::new(),
+            overflowed: false,
+            non_negative: numbers.iter().all(|number| !number.is_negative())
+                && operators
+                    .iter()
+                    .all(|operator| operator.keeps_non_negative()),
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.found.len() >= self.limit
+    }
+
+    /// Finds the operators that turn the first `count` numbers into `target`. `None` as
+    /// soon as an operator cannot be undone.
+    fn backward(&mut self, target: &N, count: usize) -> Option<()> {
+        if count == 1 {
+            if *target == self.numbers[0] {
+                self.found.push(self.chosen.clone());
+            }
+            return Some(());
+        }
+
+        let last = &self.numbers[count - 1];
+        for (index, operator) in self.operators.iter().enumerate() {
+            if self.is_full() {
+                break;
+            }
+            self.chosen[count - 2] = index;
+            match operator.inverse(target, last)? {
+                Inverse::Impossible => {}
+                // like a sum ending below its last number
+                Inverse::Left(left) if self.non_negative && left.is_negative() => {}
+                Inverse::Left(left) => self.backward(&left, count - 1)?,
+                Inverse::Any => self.forward(self.numbers[0].clone(), 1, count - 1, None),
+                Inverse::Negative if self.non_negative => {}
+                Inverse::Overflow | Inverse::Negative => self.overflowed = true,
+            }
+        }
+
+        Some(())
+    }
+
+    /// Tries every combination of the numbers up to `end` from the left, `value` being the
+    /// result of the first `count` numbers. A `target` of `None` accepts any result the
+    /// operators are defined for.
+    fn forward(&mut self, value: N, count: usize, end: usize, target: Option<&N>) {
+        if count == end {
+            if target.is_none_or(|target| *target == value) {
+                self.found.push(self.chosen.clone());
+            }
+            return;
+        }
+
+        for (index, operator) in self.operators.iter().enumerate() {
+            if self.is_full() {
+                break;
+            }
+            match operator.apply(&value, &self.numbers[count]) {
+                Ok(next) => {
+                    self.chosen[count - 1] = index;
+                    self.forward(next, count + 1, end, target);
+                }
+                Err(Failure::Undefined) => {}
+                Err(Failure::Overflow) => self.overflowed = true,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operators::{all, by_symbol, Add, Concatenate};
+    use common::Xorshift;
+    use num_bigint::BigInt;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_example() {
+        let equations = vec![
+            ("190: 10 19", true, true),
+            ("3267: 81 40 27", true, true),
+            ("83: 17 5", false, false),
+            ("156: 15 6", false, true),
+            ("7290: 6 8 6 15", false, true),
+            ("161011: 16 10 13", false, false),
+            ("192: 17 8 14", false, true),
+            ("21037: 9 7 18 13", false, false),
+            ("292: 11 6 16 20", true, true),
+        ];
+
+        for (equation, first, second) in equations {
+            let equation = Equation::from(equation);
+            assert_eq!(equation.is_solvable(false), Ok(first));
+            assert_eq!(equation.is_solvable(true), Ok(second));
+        }
+    }
+
+    #[test]
+    fn test_matches_reference() {
+        let mut xorshift = Xorshift::new(0x9e3779b9);
+        let mut random = move || xorshift.next_u32() as i128;
+
+        for _ in 0..500 {
+            let numbers: Vec<i128> = (0..2 + random() % 5).map(|_| random() % 20).collect();
+            // half of the results are built from the numbers, so many equations hold
+            let result = if random() % 2 == 0 {
+                numbers[1..]
+                    .iter()
+                    .fold(numbers[0], |result, number| match random() % 3 {
+                        0 => result + number,
+                        1 => result * number,
+                        _ => Concatenate.apply(&result, number).unwrap(),
+                    })
+            } else {
+                random() % 2000
+            };
+            let equation = Equation { result, numbers };
+
+            assert_eq!(
+                equation.is_solvable(false),
+                Ok(equation.has_solution()),
+                "{} {:?}",
+                equation.result,
+                equation.numbers
+            );
+            assert_eq!(
+                equation.is_solvable(true),
+                Ok(equation.has_solution_2()),
+                "{} {:?}",
+                equation.result,
+                equation.numbers
+            );
+        }
+    }
+
+    #[test]
+    fn test_operator_sets() {
+        let solve = |equation: &str, symbols: &[&str]| {
+            let operators: Vec<&dyn Operator<i128>> =
+                symbols.iter().map(|s| by_symbol(s).unwrap()).collect();
+            Equation::from(equation).solve(&operators).unwrap()
+        };
+
+        assert!(solve("2: 5 3", &["-"]));
+        assert!(!solve("2: 5 3", &["+", "*"]));
+        assert!(solve("6: 5 3", &["xor"]));
+        assert!(solve("3: 7 2", &["/"]));
+        assert!(solve("64: 2 3 2", &["^"]));
+        assert!(solve("17: 2 3 9", &["^", "+"]));
+        assert!(!solve("18: 2 3 9", &["^", "+"]));
+        assert!(solve("0: 7 5 0", &["+", "*"]));
+    }
+
+    #[test]
+    fn test_overflow_is_never_a_wrong_answer() {
+        // u64::MAX + 1 - u64::MAX = 1 only holds beyond u64
+        let line = "1: 18446744073709551615 1 18446744073709551615";
+        let operators = |symbols: &[&str]| -> Vec<&dyn Operator<u64>> {
+            symbols.iter().map(|s| by_symbol(s).unwrap()).collect()
+        };
+        let narrow = Equation::<u64>::parse(line).unwrap();
+        assert_eq!(narrow.solve(&operators(&["+", "-"])), Err(Overflow));
+        assert_eq!(Equation::from(line).solve(&all()), Ok(true));
+
+        // the prefix overflows i128, but multiplying by zero makes any prefix a solution
+        let line =
+            "0: 100000000000000000000000000000000000000 100000000000000000000000000000000000000 0";
+        assert_eq!(Equation::from(line).is_solvable(false), Err(Overflow));
+        let wide = Equation::<BigInt>::parse(line).unwrap();
+        assert_eq!(wide.is_solvable(false), Ok(true));
+    }
+
+    #[test]
+    fn test_long_numbers() {
+        let line = "123456789012345678901234567890123456789012345: \
+                    123456789012345678901234567890 123456789012345";
+        assert!(Equation::<i128>::parse(line).is_none());
+        let equation = Equation::<BigInt>::parse(line).unwrap();
+        assert_eq!(equation.is_solvable(false), Ok(false));
+        assert_eq!(equation.is_solvable(true), Ok(true));
+    }
+
+    #[test]
+    fn test_number_types_agree() {
+        let mut xorshift = Xorshift::new(0x1b873593);
+        let mut random = move || xorshift.next_u32() as u64;
+
+        for _ in 0..200 {
+            let numbers: Vec<u64> = (0..2 + random() % 5).map(|_| random() % 100).collect();
+            let result = random() % 100_000;
+            let line = format!(
+                "{}: {}",
+                result,
+                numbers
+                    .iter()
+                    .map(u64::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            );
+
+            let expected = Equation::<BigInt>::parse(&line).unwrap().is_solvable(true);
+            assert_eq!(
+                Equation::<u64>::parse(&line).unwrap().is_solvable(true),
+                expected
+            );
+            assert_eq!(
+                Equation::<i128>::parse(&line).unwrap().is_solvable(true),
+                expected
+            );
+        }
+
+        // 1 - 2 + 1 goes below zero on the way, which u64 cannot hold
+        fn solve<N: Number>(line: &str) -> Result<bool, Overflow> {
+            let operators = [by_symbol("-").unwrap(), by_symbol("+").unwrap()];
+            Equation::<N>::parse(line).unwrap().solve(&operators)
+        }
+        assert_eq!(solve::<i128>("0: 1 2 1"), Ok(true));
+        assert_eq!(solve::<BigInt>("0: 1 2 1"), Ok(true));
+        assert_eq!(solve::<u64>("0: 1 2 1"), Err(Overflow));
+    }
+
+    #[test]
+    fn test_backward_matches_forward() {
+        let mut xorshift = Xorshift::new(0x2545f491);
+        let mut random = move || xorshift.next_u32() as i128;
+
+        for _ in 0..300 {
+            let numbers: Vec<i128> = (0..2 + random() % 4).map(|_| random() % 12).collect();
+            let result = random() % 200 - 50;
+            // every subset of the invertible operators
+            let invertible: Vec<&dyn Operator<i128>> = all()
+                .into_iter()
+                .filter(|operator| operator.inverse(&0, &1).is_some())
+                .collect();
+            let operators: Vec<&dyn Operator<i128>> = invertible
+                .iter()
+                .copied()
+                .filter(|_| random() % 2 == 0)
+                .collect();
+
+            let mut backward = Search::new(&operators, &numbers, usize::MAX);
+            let mut forward = Search::new(&operators, &numbers, usize::MAX);
+            assert!(backward.backward(&result, numbers.len()).is_some());
+            forward.forward(numbers[0], 1, numbers.len(), Some(&result));
+            backward.found.sort();
+            assert_eq!(
+                backward.found,
+                forward.found,
+                "{result}: {numbers:?} with {:?}",
+                operators.iter().map(|o| o.symbol()).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    /// Addition that counts how often the backward solver undoes it.
+    struct CountingAdd(AtomicUsize);
+
+    impl Operator<i128> for CountingAdd {
+        fn symbol(&self) -> &'static str {
+            "+"
+        }
+
+        fn precedence(&self) -> u8 {
+            2
+        }
+
+        fn apply(&self, left: &i128, right: &i128) -> Result<i128, Failure> {
+            Add.apply(left, right)
+        }
+
+        fn inverse(&self, result: &i128, right: &i128) -> Option<Inverse<i128>> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            Add.inverse(result, right)
+        }
+
+        fn keeps_non_negative(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_backward_prunes_negative_partials() {
+        let equation = Equation::from("5: 1 2 10");
+
+        // 5 - 10 is negative, so 1 + 2 is never tried
+        let add = CountingAdd(AtomicUsize::new(0));
+        assert_eq!(equation.solve(&[&add]), Ok(false));
+        assert_eq!(add.0.load(Ordering::Relaxed), 1);
+
+        // with subtraction a partial result may be negative
+        let add = CountingAdd(AtomicUsize::new(0));
+        assert_eq!(equation.solve(&[&add, by_symbol("-").unwrap()]), Ok(false));
+        assert_eq!(add.0.load(Ordering::Relaxed), 3);
+    }
+}
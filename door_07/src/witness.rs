@@ -0,0 +1,91 @@
+use crate::number::Number;
+use crate::operators::Operator;
+use crate::solver::{search, Overflow};
+use crate::Equation;
+use std::fmt;
+
+/// Operators that make an equation hold, written out like `6 * 8 || 6 * 15 = 7290`.
+#[derive(Debug, PartialEq)]
+pub struct Witness<N> {
+    pub result: N,
+    pub numbers: Vec<N>,
+    pub symbols: Vec<&'static str>,
+}
+
+impl<N: fmt::Display> fmt::Display for Witness<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.numbers[0])?;
+        for (symbol, number) in self.symbols.iter().zip(&self.numbers[1..]) {
+            write!(f, " {} {}", symbol, number)?;
+        }
+        write!(f, " = {}", self.result)
+    }
+}
+
+impl<N: Number> Equation<N> {
+    fn witnesses_up_to(
+        &self,
+        operators: &[&dyn Operator<N>],
+        limit: usize,
+    ) -> Result<Vec<Witness<N>>, Overflow> {
+        Ok(search(self, operators, limit)?
+            .into_iter()
+            .map(|chosen| Witness {
+                result: self.result.clone(),
+                numbers: self.numbers.clone(),
+                symbols: chosen.iter().map(|&i| operators[i].symbol()).collect(),
+            })
+            .collect())
+    }
+
+    pub fn witness(&self, operators: &[&dyn Operator<N>]) -> Result<Option<Witness<N>>, Overflow> {
+        Ok(self.witnesses_up_to(operators, 1)?.pop())
+    }
+
+    pub fn witnesses(&self, operators: &[&dyn Operator<N>]) -> Result<Vec<Witness<N>>, Overflow> {
+        self.witnesses_up_to(operators, usize::MAX)
+    }
+
+    /// Number of distinct operator assignments that make the equation hold.
+    pub fn count_solutions(&self, operators: &[&dyn Operator<N>]) -> Result<usize, Overflow> {
+        Ok(search(self, operators, usize::MAX)?.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operators::{part_one, part_two};
+
+    #[test]
+    fn test_witness() {
+        let equation = Equation::from("7290: 6 8 6 15");
+        assert_eq!(
+            equation.witness(&part_two()).unwrap().unwrap().to_string(),
+            "6 * 8 || 6 * 15 = 7290"
+        );
+        assert_eq!(equation.witness(&part_one()), Ok(None));
+        assert_eq!(equation.count_solutions(&part_one()), Ok(0));
+    }
+
+    #[test]
+    fn test_all_witnesses() {
+        let equation = Equation::from("3267: 81 40 27");
+        let witnesses: Vec<String> = equation
+            .witnesses(&part_one())
+            .unwrap()
+            .iter()
+            .map(Witness::to_string)
+            .collect();
+        assert_eq!(Ok(witnesses.len()), equation.count_solutions(&part_one()));
+        assert!(witnesses.contains(&String::from("81 + 40 * 27 = 3267")));
+        assert!(witnesses.contains(&String::from("81 * 40 + 27 = 3267")));
+        assert_eq!(witnesses.len(), 2);
+
+        // multiplying by zero makes everything before it a solution
+        assert_eq!(
+            Equation::from("0: 1 2 0").count_solutions(&part_two()),
+            Ok(3)
+        );
+    }
+}
@@ -0,0 +1,72 @@
+use crate::{AntennasMap, Point, harmonics};
+use std::collections::{BTreeSet, HashMap};
+
+/// Where antennas of the same frequency create antinodes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Model {
+    /// Part one: one antinode on either side of a pair, as far away as the antennas are apart.
+    Pairs,
+    /// Part two: every position in line with a pair, see `harmonics::line` for `reduce`.
+    Harmonics { reduce: bool },
+}
+
+/// The antinodes on the map with the frequencies that create them.
+pub type Antinodes<const D: usize = 2> = HashMap<Point<D>, BTreeSet<char>>;
+
+impl<const D: usize> AntennasMap<D> {
+    pub fn antinodes(&self, model: Model) -> Antinodes<D> {
+        let mut antinodes = Antinodes::new();
+        for (frequency, positions) in &self.antennas {
+            for (index, position) in positions.iter().enumerate() {
+                for other in &positions[index + 1..] {
+                    let found = match model {
+                        Model::Pairs => vec![
+                            position.calculate_antinode_position(&other.distance(position)),
+                            other.calculate_antinode_position(&position.distance(other)),
+                        ],
+                        Model::Harmonics { reduce } => {
+                            harmonics::line(self, position, other, reduce)
+                        }
+                    };
+                    for antinode in found.into_iter().filter(|p| self.is_inside(p)) {
+                        antinodes.entry(antinode).or_default().insert(*frequency);
+                    }
+                }
+            }
+        }
+        antinodes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Position;
+
+    fn map() -> AntennasMap {
+        let mut antennas = HashMap::new();
+        antennas.insert('a', vec![Position::new(3, 1), Position::new(4, 3)]);
+        antennas.insert('A', vec![Position::new(6, 6), Position::new(7, 7)]);
+        AntennasMap {
+            size: [10, 10],
+            antennas,
+        }
+    }
+
+    #[test]
+    fn test_antinodes_are_tagged_by_frequency() {
+        let antinodes = map().antinodes(Model::Pairs);
+        // the other antinode of the `a` pair is off the map
+        assert_eq!(antinodes.len(), 2);
+        assert_eq!(antinodes[&Position::new(5, 5)], BTreeSet::from(['a', 'A']));
+        assert_eq!(antinodes[&Position::new(8, 8)], BTreeSet::from(['A']));
+    }
+
+    #[test]
+    fn test_harmonics_include_the_antennas() {
+        let antinodes = map().antinodes(Model::Harmonics { reduce: false });
+        assert_eq!(antinodes.len(), 5 + 10 - 1);
+        assert_eq!(antinodes[&Position::new(3, 1)], BTreeSet::from(['a']));
+        assert_eq!(antinodes[&Position::new(5, 5)], BTreeSet::from(['a', 'A']));
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{AntennasMap, Distance, Point};
+
+fn gcd(a: i32, b: i32) -> i32 {
+    if b == 0 { a.abs() } else { gcd(b, a % b) }
+}
+
+/// Every position on the map in line with the antennas at `a` and `b`, stepping by their
+/// distance from `a` in both directions. With `reduce` the step is the distance divided
+/// by the gcd of its coordinates, so the grid points between the antennas count as well.
+pub fn line<const D: usize>(
+    map: &AntennasMap<D>,
+    a: &Point<D>,
+    b: &Point<D>,
+    reduce: bool,
+) -> Vec<Point<D>> {
+    let distance = b.distance(a);
+    let divisor = if reduce {
+        distance
+            .0
+            .iter()
+            .fold(0, |divisor, &c| gcd(divisor, c))
+            .max(1)
+    } else {
+        1
+    };
+    let step = distance.0.map(|coordinate| coordinate / divisor);
+    if step.iter().all(|&coordinate| coordinate == 0) {
+        return vec![a.clone()];
+    }
+
+    // an antinode position is one step away from b
+    let away_from_b = Distance(step);
+    let towards_b = Distance(step).rev();
+    let behind_a = a.calculate_antinode_position(&away_from_b);
+
+    let mut positions = Vec::new();
+    for (mut position, step) in [(a.clone(), towards_b), (behind_a, away_from_b)] {
+        while map.is_inside(&position) {
+            positions.push(position.clone());
+            position = position.calculate_antinode_position(&step);
+        }
+    }
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Position;
+
+    fn empty_map<const D: usize>(size: [usize; D]) -> AntennasMap<D> {
+        AntennasMap {
+            size,
+            antennas: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_gcd() {
+        assert_eq!(gcd(4, 6), 2);
+        assert_eq!(gcd(-4, 6), 2);
+        assert_eq!(gcd(0, -3), 3);
+    }
+
+    #[test]
+    fn test_line() {
+        let map = empty_map([5, 5]);
+        let a = Position::new(0, 0);
+        let b = Position::new(2, 2);
+
+        let mut multiples = line(&map, &a, &b, false);
+        multiples.sort();
+        assert_eq!(
+            multiples,
+            vec![
+                Position::new(0, 0),
+                Position::new(2, 2),
+                Position::new(4, 4)
+            ]
+        );
+        assert_eq!(line(&map, &a, &b, true).len(), 5);
+        // the line is the same from either end
+        assert_eq!(line(&map, &b, &a, true).len(), 5);
+    }
+
+    #[test]
+    fn test_line_stays_on_the_map() {
+        let map = empty_map([4, 3]);
+        let positions = line(&map, &Position::new(3, 0), &Position::new(2, 1), false);
+        assert!(positions.iter().all(|position| map.is_inside(position)));
+        assert_eq!(positions.len(), 3);
+    }
+
+    #[test]
+    fn test_line_in_three_dimensions() {
+        let map = empty_map([7, 7, 7]);
+        let a = Point([0, 1, 2]);
+        let b = Point([2, 3, 6]);
+        assert_eq!(line(&map, &a, &b, false).len(), 2);
+        assert_eq!(
+            line(&map, &a, &b, true),
+            vec![Point([0, 1, 2]), Point([1, 2, 4]), Point([2, 3, 6])]
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use crate::parse::ParseError;
+use crate::{AntennasMap, Point};
+use std::collections::HashMap;
+
+impl AntennasMap<3> {
+    /// A stack of 2D slices separated by blank lines; the first slice is at z = 0. Every
+    /// slice is parsed like a 2D grid and must be as large as the first one.
+    pub fn from_layers<'a>(lines: impl IntoIterator<Item = &'a str>) -> Result<Self, ParseError> {
+        let mut layers: Vec<Vec<&str>> = vec![Vec::new()];
+        for line in lines {
+            if !line.trim().is_empty() {
+                layers.last_mut().unwrap().push(line);
+            } else if !layers.last().unwrap().is_empty() {
+                layers.push(Vec::new());
+            }
+        }
+        if layers.last().unwrap().is_empty() {
+            layers.pop();
+        }
+
+        let mut size = [0, 0, layers.len()];
+        let mut antennas: HashMap<char, Vec<Point<3>>> = HashMap::new();
+        for (z, layer) in layers.into_iter().enumerate() {
+            let slice = AntennasMap::grid(&layer)?;
+            if z == 0 {
+                size[..2].copy_from_slice(&slice.size);
+            } else if slice.size != size[..2] {
+                return Err(ParseError::MismatchedLayer {
+                    z,
+                    size: slice.size,
+                    expected: [size[0], size[1]],
+                });
+            }
+            for (frequency, positions) in slice.antennas {
+                antennas.entry(frequency).or_default().extend(
+                    positions
+                        .into_iter()
+                        .map(|Point([x, y])| Point([x, y, z as i32])),
+                );
+            }
+        }
+
+        Ok(Self { size, antennas })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::antinodes::Model;
+
+    const EXAMPLE: &str = "....
+.a..
+....
+
+....
+..a.
+....
+
+
+....
+....
+....
+";
+
+    #[test]
+    fn test_from_layers() {
+        let map = AntennasMap::from_layers(EXAMPLE.lines()).unwrap();
+        assert_eq!(map.size, [4, 3, 3]);
+        assert_eq!(map.antennas[&'a'], vec![Point([1, 1, 0]), Point([2, 1, 1])]);
+    }
+
+    #[test]
+    fn test_mismatched_layers() {
+        let error = AntennasMap::from_layers("....\n.a..\n\n...\n...".lines())
+            .err()
+            .unwrap();
+        assert_eq!(
+            error,
+            ParseError::MismatchedLayer {
+                z: 1,
+                size: [3, 2],
+                expected: [4, 2]
+            }
+        );
+        assert_eq!(error.to_string(), "layer 1 is 3x2 positions instead of 4x2");
+    }
+
+    #[test]
+    fn test_antinodes_in_three_dimensions() {
+        let map = AntennasMap::from_layers(EXAMPLE.lines()).unwrap();
+        let antinodes = map.antinodes(Model::Pairs);
+        // the other antinode is at z = -1
+        assert_eq!(antinodes.len(), 1);
+        assert!(antinodes.contains_key(&Point([3, 1, 2])));
+        assert_eq!(map.antinodes(Model::Harmonics { reduce: false }).len(), 3);
+    }
+
+    #[test]
+    fn test_single_layer_matches_the_plane() {
+        let plane = "..a.\n....\na...\n....";
+        let flat = AntennasMap::parse(plane.as_bytes()).unwrap();
+        let layered = AntennasMap::from_layers(plane.lines()).unwrap();
+        assert_eq!(layered.size, [4, 4, 1]);
+        assert_eq!(
+            layered.antinodes(Model::Pairs).len(),
+            flat.antinodes(Model::Pairs).len()
+        );
+    }
+}
@@ -1,217 +1,152 @@
-use common::read_lines;
-use std::collections::{HashMap, HashSet};
+mod antinodes;
+mod harmonics;
+mod layers;
+mod parse;
+mod render;
+mod stats;
+
+use antinodes::Model;
+use parse::ParseError;
+use render::{Frequencies, Renderer};
+use std::collections::HashMap;
 use std::fs::File;
-use std::io;
+use std::io::BufReader;
 
 fn main() {
-    let lines = read_lines("door_08/input.txt").unwrap();
-    let map = AntennasMap::from_lines_with_bufreader(lines);
-    print!("{}", map.count_antennas_2())
-}
+    let mut model = Model::Harmonics { reduce: false };
+    let mut output = None;
+    let mut frequencies = Frequencies::All;
+    let mut layers = None;
+    let mut report = None;
+    let mut input = String::from("door_08/input.txt");
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--pairs" => model = Model::Pairs,
+            // also counts the grid points between two antennas of a line
+            "--gcd" => model = Model::Harmonics { reduce: true },
+            "--ascii" | "--ansi" => output = Some(arg),
+            "--stats" | "--stats=table" | "--stats=json" => report = Some(arg),
+            _ => {
+                if let Some(only) = arg.strip_prefix("--frequency=") {
+                    frequencies = Frequencies::Only(only.chars().collect());
+                } else if let Some(path) = arg.strip_prefix("--layers=") {
+                    layers = Some(path.to_string());
+                } else if let Some(path) = arg.strip_prefix("--map=") {
+                    input = path.to_string();
+                }
+            }
+        }
+    }
 
-#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Hash)]
-struct Position {
-    x: i32,
-    y: i32,
-}
+    // a 3D map is only counted, the renderer draws planes
+    if let Some(path) = layers {
+        match std::fs::read_to_string(&path)
+            .map_err(ParseError::from)
+            .and_then(|text| AntennasMap::from_layers(text.lines()))
+        {
+            Ok(map) => print!("{}", map.antinodes(model).len()),
+            Err(error) => eprintln!("{path}: {error}"),
+        }
+        return;
+    }
 
-struct Distance {
-    x: i32,
-    y: i32,
-}
+    // a grid or a list of `frequency x y` records
+    let map = match File::open(&input)
+        .map_err(ParseError::from)
+        .and_then(|file| AntennasMap::parse(BufReader::new(file)))
+    {
+        Ok(map) => map,
+        Err(error) => return eprintln!("{input}: {error}"),
+    };
+
+    if let Some(report) = report {
+        let statistics = map.statistics();
+        match report.as_str() {
+            "--stats=json" => print!("{}", stats::json(&statistics)),
+            _ => print!("{}", stats::table(&statistics)),
+        }
+        return;
+    }
 
-impl Distance {
-    fn new(x: i32, y: i32) -> Self {
-        Self { x, y }
+    let antinodes = map.antinodes(model);
+    let renderer = Renderer::new(&map, &antinodes);
+    match output.as_deref() {
+        Some("--ascii") => print!("{}", renderer.ascii(&frequencies)),
+        Some("--ansi") => print!("{}", renderer.ansi(&frequencies)),
+        _ => {}
     }
+    print!("{}", antinodes.len())
+}
+
+/// A point on a grid with `D` axes.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Hash)]
+struct Point<const D: usize>([i32; D]);
+
+/// The points of the puzzle maps.
+type Position = Point<2>;
 
+struct Distance<const D: usize>([i32; D]);
+
+impl<const D: usize> Distance<D> {
     fn rev(self) -> Self {
-        Self {
-            x: self.x * -1,
-            y: self.y * -1,
-        }
+        Self(self.0.map(|coordinate| -coordinate))
     }
 }
 
 impl Position {
     pub fn new(x: i32, y: i32) -> Self {
-        Self { x, y }
+        Self([x, y])
     }
+}
 
-    pub fn distance(&self, other: &Position) -> Distance {
-        Distance::new(self.x - other.x, self.y - other.y)
+impl<const D: usize> Point<D> {
+    pub fn distance(&self, other: &Self) -> Distance<D> {
+        Distance(std::array::from_fn(|axis| self.0[axis] - other.0[axis]))
     }
 
-    pub fn calculate_antinode_position(&self, distance: &Distance) -> Self {
-        Self {
-            x: self.x - distance.x,
-            y: self.y - distance.y,
-        }
+    pub fn calculate_antinode_position(&self, distance: &Distance<D>) -> Self {
+        Self(std::array::from_fn(|axis| self.0[axis] - distance.0[axis]))
     }
 }
 
-struct AntennasMap {
-    pub width: usize,
-    pub height: usize,
-    pub antennas: HashMap<char, Vec<Position>>,
+struct AntennasMap<const D: usize = 2> {
+    /// The number of points along each axis.
+    pub size: [usize; D],
+    pub antennas: HashMap<char, Vec<Point<D>>>,
 }
 
 impl AntennasMap {
-    fn from_lines_with_bufreader(lines: io::Lines<io::BufReader<File>>) -> Self {
-        let mut width: usize = 0;
-        let mut height = 0;
-        let mut antennas = HashMap::new();
-
-        for (y, line) in lines.enumerate() {
-            let line = line.unwrap();
-            width = line.len();
-            for (x, entry) in line.chars().enumerate() {
-                if entry.is_ascii_alphabetic() || entry.is_ascii_digit() {
-                    let positions = antennas.entry(entry).or_insert(Vec::new());
-                    positions.push(Position::new(x as i32, y as i32));
-                }
-            }
-            height += 1;
-        }
-
-        Self {
-            width,
-            height,
-            antennas,
-        }
+    fn width(&self) -> usize {
+        self.size[0]
     }
 
-    fn from_lines(lines: core::str::Lines) -> Self {
-        let mut width: usize = 0;
-        let mut height = 0;
-        let mut antennas = HashMap::new();
-
-        for (y, line) in lines.enumerate() {
-            width = line.len();
-            for (x, entry) in line.chars().enumerate() {
-                if entry.is_ascii_alphabetic() || entry.is_ascii_digit() {
-                    let positions = antennas.entry(entry).or_insert(Vec::new());
-                    positions.push(Position::new(x as i32, y as i32));
-                }
-            }
-            height += 1;
-        }
-
-        Self {
-            width,
-            height,
-            antennas,
-        }
+    fn height(&self) -> usize {
+        self.size[1]
     }
+}
 
+impl<const D: usize> AntennasMap<D> {
     fn count_antennas(&self) -> usize {
-        let mut anitnode_positions = HashSet::new();
-        for (name, positions) in &self.antennas {
-            for position in positions {
-                for other in positions {
-                    if position == other {
-                        continue;
-                    }
-
-                    let distance = position.distance(other).rev();
-                    let antinode_pos = position.calculate_antinode_position(&distance);
-                    anitnode_positions.insert(antinode_pos.clone());
-                }
-            }
-        }
-
-        let mut count = 0;
-        for y in 0..self.height {
-            let mut dbg = String::new();
-            for x in 0..self.width {
-                let p = Position::new(x as i32, y as i32);
-                if anitnode_positions.contains(&p) {
-                    dbg.push('#');
-                    count += 1;
-                    continue;
-                }
-                dbg.push('.')
-            }
-            println!("{dbg}")
-        }
-
-        count
+        self.antinodes(Model::Pairs).len()
     }
 
+    #[cfg(test)]
     fn count_antennas_2(&self) -> usize {
-        let mut anitnode_positions = HashSet::new();
-        for (name, positions) in &self.antennas {
-            for position in positions {
-                for other in positions {
-                    if position == other {
-                        continue;
-                    }
-
-                    let distance = position.distance(other).rev();
-
-                    let mut pos = position.clone();
-                    loop {
-                        let antinode_pos = pos.calculate_antinode_position(&distance);
-                        anitnode_positions.insert(antinode_pos.clone());
-                        pos = antinode_pos;
-
-                        if pos.x < 0
-                            || pos.x >= self.width as i32 && pos.y < 0
-                            || pos.y >= self.height as i32
-                        {
-                            break;
-                        }
-                    }
-
-                    let distance = position.distance(other);
-
-                    let mut pos = position.clone();
-                    loop {
-                        let antinode_pos = pos.calculate_antinode_position(&distance);
-                        anitnode_positions.insert(antinode_pos.clone());
-                        pos = antinode_pos;
-
-                        if pos.x < 0
-                            || pos.x >= self.width as i32 && pos.y < 0
-                            || pos.y >= self.height as i32
-                        {
-                            break;
-                        }
-                    }
-                }
-            }
-        }
-
-        let mut count = 0;
-        for y in 0..self.height {
-            let mut dbg = String::new();
-            for x in 0..self.width {
-                let p = Position::new(x as i32, y as i32);
-                if anitnode_positions.contains(&p) {
-                    dbg.push('#');
-                    count += 1;
-                    continue;
-                }
-                dbg.push('.')
-            }
-            println!("{dbg}")
-        }
-
-        count
+        self.antinodes(Model::Harmonics { reduce: false }).len()
     }
 
-    fn is_inside(&self, position: &Position) -> bool {
-        position.x >= 0
-            && position.x < self.width as i32
-            && position.y >= 0
-            && position.y < self.height as i32
+    fn is_inside(&self, position: &Point<D>) -> bool {
+        position
+            .0
+            .iter()
+            .zip(self.size)
+            .all(|(&coordinate, size)| coordinate >= 0 && coordinate < size as i32)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use common::read_lines;
 
     const example: &'static str = r#"............
 ........0...
@@ -228,19 +163,17 @@ mod tests {
 
     #[test]
     fn create_map() {
-        let lines = example.lines();
-        let antennas = AntennasMap::from_lines(lines);
-        assert_eq!(antennas.width, 12);
-        assert_eq!(antennas.height, 12);
+        let antennas = AntennasMap::parse(example.as_bytes()).unwrap();
+        assert_eq!(antennas.width(), 12);
+        assert_eq!(antennas.height(), 12);
         assert_eq!(antennas.antennas.len(), 2);
     }
 
     #[test]
     fn count_antinode_positions_with_simple_example() {
-        let lines = example.lines();
-        let antennas = AntennasMap::from_lines(lines);
-        assert_eq!(antennas.width, 12);
-        assert_eq!(antennas.height, 12);
+        let antennas = AntennasMap::parse(example.as_bytes()).unwrap();
+        assert_eq!(antennas.width(), 12);
+        assert_eq!(antennas.height(), 12);
 
         let count = antennas.count_antennas();
         assert_eq!(count, 14);
@@ -248,10 +181,9 @@ mod tests {
 
     #[test]
     fn count_antinode_positions_2_with_simple_example() {
-        let lines = example.lines();
-        let antennas = AntennasMap::from_lines(lines);
-        assert_eq!(antennas.width, 12);
-        assert_eq!(antennas.height, 12);
+        let antennas = AntennasMap::parse(example.as_bytes()).unwrap();
+        assert_eq!(antennas.width(), 12);
+        assert_eq!(antennas.height(), 12);
 
         let count = antennas.count_antennas_2();
         assert_eq!(count, 34);
@@ -259,11 +191,11 @@ mod tests {
 
     #[test]
     fn create_map_from_input() {
-        let path = "/home/milg/RustroverProjects/advent_of_code_2024/door_08/input_test.txt";
-        let lines = read_lines(path).unwrap();
-        let antennas = AntennasMap::from_lines_with_bufreader(lines);
-        assert_eq!(antennas.width, 12);
-        assert_eq!(antennas.height, 12);
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/input_test.txt");
+        let file = File::open(path).unwrap();
+        let antennas = AntennasMap::parse(BufReader::new(file)).unwrap();
+        assert_eq!(antennas.width(), 12);
+        assert_eq!(antennas.height(), 12);
         assert_eq!(antennas.antennas.len(), 2);
     }
 }
@@ -0,0 +1,250 @@
+use crate::{AntennasMap, Position};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::io::{self, BufRead};
+
+/// Why a text is not an antenna map.
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    Io(io::ErrorKind),
+    /// A row of the grid is longer or shorter than the first one.
+    Ragged {
+        y: usize,
+        width: usize,
+        expected: usize,
+    },
+    /// Neither an antenna, an empty position nor a drawn antinode.
+    UnknownGlyph {
+        glyph: char,
+        position: Position,
+    },
+    /// A slice of a layered map whose width or height differs from the first slice.
+    MismatchedLayer {
+        z: usize,
+        size: [usize; 2],
+        expected: [usize; 2],
+    },
+    /// A line of the record format that is not `size width height` or `frequency x y`.
+    InvalidRecord {
+        line: usize,
+        text: String,
+    },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Io(kind) => write!(f, "cannot read the map: {kind}"),
+            ParseError::Ragged { y, width, expected } => {
+                write!(f, "row {y} is {width} positions wide instead of {expected}")
+            }
+            ParseError::UnknownGlyph { glyph, position } => {
+                let [x, y] = position.0;
+                write!(f, "unknown glyph {glyph:?} at ({x}, {y})")
+            }
+            ParseError::MismatchedLayer { z, size, expected } => write!(
+                f,
+                "layer {z} is {}x{} positions instead of {}x{}",
+                size[0], size[1], expected[0], expected[1]
+            ),
+            ParseError::InvalidRecord { line, text } => {
+                write!(f, "line {line} is not a valid record: {text:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<io::Error> for ParseError {
+    fn from(error: io::Error) -> Self {
+        ParseError::Io(error.kind())
+    }
+}
+
+fn is_antenna(glyph: char) -> bool {
+    glyph.is_ascii_alphanumeric()
+}
+
+impl AntennasMap {
+    /// Reads either a grid of `.` and antennas, or a list of `frequency x y` records
+    /// optionally preceded by a `size width height` record; without it the map ends just
+    /// after the last antenna. A grid may show antinodes as `#`, they are ignored.
+    ///
+    /// A `&str` is read through its bytes: `AntennasMap::parse(text.as_bytes())`.
+    pub fn parse(reader: impl BufRead) -> Result<Self, ParseError> {
+        let mut lines = reader.lines().collect::<Result<Vec<_>, _>>()?;
+        while lines.last().is_some_and(|line| line.trim().is_empty()) {
+            lines.pop();
+        }
+
+        let is_record = |line: &String| line.split_whitespace().count() > 1;
+        if lines.first().is_some_and(is_record) {
+            Self::records(&lines)
+        } else {
+            Self::grid(&lines)
+        }
+    }
+
+    pub(crate) fn grid(lines: &[impl AsRef<str>]) -> Result<Self, ParseError> {
+        let width = lines
+            .first()
+            .map_or(0, |line| line.as_ref().chars().count());
+        let mut antennas: HashMap<char, Vec<Position>> = HashMap::new();
+
+        for (y, line) in lines.iter().enumerate() {
+            let line = line.as_ref();
+            let row_width = line.chars().count();
+            if row_width != width {
+                return Err(ParseError::Ragged {
+                    y,
+                    width: row_width,
+                    expected: width,
+                });
+            }
+            for (x, glyph) in line.chars().enumerate() {
+                let position = Position::new(x as i32, y as i32);
+                match glyph {
+                    '.' | '#' => {}
+                    glyph if is_antenna(glyph) => antennas.entry(glyph).or_default().push(position),
+                    glyph => return Err(ParseError::UnknownGlyph { glyph, position }),
+                }
+            }
+        }
+
+        Ok(Self {
+            size: [width, lines.len()],
+            antennas,
+        })
+    }
+
+    fn records(lines: &[String]) -> Result<Self, ParseError> {
+        let mut size = None;
+        let mut antennas: HashMap<char, Vec<Position>> = HashMap::new();
+
+        for (index, line) in lines.iter().enumerate() {
+            let invalid = || ParseError::InvalidRecord {
+                line: index + 1,
+                text: line.clone(),
+            };
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.is_empty() {
+                continue;
+            }
+            let [name, x, y] = fields[..] else {
+                return Err(invalid());
+            };
+            let (Ok(x), Ok(y)) = (x.parse::<i32>(), y.parse::<i32>()) else {
+                return Err(invalid());
+            };
+            if x < 0 || y < 0 {
+                return Err(invalid());
+            }
+
+            let mut glyphs = name.chars();
+            match (glyphs.next(), glyphs.next()) {
+                _ if name == "size" && index == 0 => size = Some([x as usize, y as usize]),
+                (Some(glyph), None) if is_antenna(glyph) => {
+                    if size
+                        .is_some_and(|[width, height]| x as usize >= width || y as usize >= height)
+                    {
+                        return Err(invalid());
+                    }
+                    antennas.entry(glyph).or_default().push(Position::new(x, y));
+                }
+                _ => return Err(invalid()),
+            }
+        }
+
+        let size = size.unwrap_or_else(|| {
+            let extent = |axis: usize| {
+                antennas
+                    .values()
+                    .flatten()
+                    .map(|position| position.0[axis] as usize + 1)
+                    .max()
+                    .unwrap_or(0)
+            };
+            [extent(0), extent(1)]
+        });
+
+        Ok(Self { size, antennas })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid() {
+        let map = AntennasMap::parse("..a.\n.#..\nA...\n\n".as_bytes()).unwrap();
+        assert_eq!(map.size, [4, 3]);
+        assert_eq!(map.antennas[&'a'], vec![Position::new(2, 0)]);
+        assert_eq!(map.antennas[&'A'], vec![Position::new(0, 2)]);
+    }
+
+    #[test]
+    fn test_invalid_grids() {
+        assert_eq!(
+            AntennasMap::parse("....\n...\n....".as_bytes()).err(),
+            Some(ParseError::Ragged {
+                y: 1,
+                width: 3,
+                expected: 4
+            })
+        );
+
+        let error = AntennasMap::parse("....\n..?.".as_bytes()).err().unwrap();
+        assert_eq!(
+            error,
+            ParseError::UnknownGlyph {
+                glyph: '?',
+                position: Position::new(2, 1)
+            }
+        );
+        assert_eq!(error.to_string(), "unknown glyph '?' at (2, 1)");
+    }
+
+    #[test]
+    fn test_records() {
+        let grid = AntennasMap::parse("..a.\n....\na..A".as_bytes()).unwrap();
+        let records = AntennasMap::parse("size 4 3\na 2 0\na 0 2\nA 3 2\n".as_bytes()).unwrap();
+        assert_eq!(records.size, grid.size);
+        assert_eq!(records.antennas, grid.antennas);
+
+        // without a size the map ends at the last antenna
+        let records = AntennasMap::parse("a 2 0\n\na 0 2\n".as_bytes()).unwrap();
+        assert_eq!(records.size, [3, 3]);
+    }
+
+    #[test]
+    fn test_invalid_records() {
+        let invalid = |line: usize, text: &str| {
+            Some(ParseError::InvalidRecord {
+                line,
+                text: String::from(text),
+            })
+        };
+        assert_eq!(
+            AntennasMap::parse("a 1 1\nab 2 2".as_bytes()).err(),
+            invalid(2, "ab 2 2")
+        );
+        assert_eq!(
+            AntennasMap::parse("a 1 -1".as_bytes()).err(),
+            invalid(1, "a 1 -1")
+        );
+        assert_eq!(
+            AntennasMap::parse("size 2 2\na 2 0".as_bytes()).err(),
+            invalid(2, "a 2 0")
+        );
+        assert_eq!(
+            AntennasMap::parse("a 1 1\nsize 2 2".as_bytes()).err(),
+            invalid(2, "size 2 2")
+        );
+        assert_eq!(
+            AntennasMap::parse("a 1 1 1".as_bytes()).err(),
+            invalid(1, "a 1 1 1")
+        );
+    }
+}
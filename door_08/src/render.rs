@@ -0,0 +1,169 @@
+use crate::antinodes::Antinodes;
+use crate::{AntennasMap, Position};
+use std::collections::{BTreeSet, HashMap};
+
+const ANSI_RESET: &str = "\x1b[0m";
+const ANSI_DIM: &str = "\x1b[2m";
+const ANSI_COLOURS: [&str; 6] = [
+    "\x1b[1;31m",
+    "\x1b[1;32m",
+    "\x1b[1;33m",
+    "\x1b[1;34m",
+    "\x1b[1;35m",
+    "\x1b[1;36m",
+];
+/// Antinodes of several frequencies.
+const ANSI_SHARED: &str = "\x1b[1;37m";
+
+pub enum Frequencies {
+    All,
+    Only(BTreeSet<char>),
+}
+
+impl Frequencies {
+    fn contains(&self, frequency: char) -> bool {
+        match self {
+            Frequencies::All => true,
+            Frequencies::Only(frequencies) => frequencies.contains(&frequency),
+        }
+    }
+}
+
+enum Cell {
+    Empty,
+    Antenna(char),
+    Antinode(Vec<char>),
+}
+
+/// Overlays the antennas and their antinodes on the map; an antenna hides an antinode on
+/// the same position.
+pub struct Renderer<'a> {
+    map: &'a AntennasMap,
+    antinodes: &'a Antinodes,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(map: &'a AntennasMap, antinodes: &'a Antinodes) -> Self {
+        Self { map, antinodes }
+    }
+
+    fn cells(&self, frequencies: &Frequencies) -> Vec<Vec<Cell>> {
+        let antennas: HashMap<&Position, char> = self
+            .map
+            .antennas
+            .iter()
+            .filter(|(frequency, _)| frequencies.contains(**frequency))
+            .flat_map(|(frequency, positions)| positions.iter().map(|p| (p, *frequency)))
+            .collect();
+
+        (0..self.map.height() as i32)
+            .map(|y| {
+                (0..self.map.width() as i32)
+                    .map(|x| {
+                        let position = Position::new(x, y);
+                        if let Some(frequency) = antennas.get(&position) {
+                            return Cell::Antenna(*frequency);
+                        }
+                        let shown: Vec<char> = self
+                            .antinodes
+                            .get(&position)
+                            .into_iter()
+                            .flatten()
+                            .copied()
+                            .filter(|frequency| frequencies.contains(*frequency))
+                            .collect();
+                        if shown.is_empty() {
+                            Cell::Empty
+                        } else {
+                            Cell::Antinode(shown)
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    pub fn ascii(&self, frequencies: &Frequencies) -> String {
+        let mut output = String::new();
+        for row in self.cells(frequencies) {
+            for cell in row {
+                output.push(match cell {
+                    Cell::Empty => '.',
+                    Cell::Antenna(frequency) => frequency,
+                    Cell::Antinode(_) => '#',
+                });
+            }
+            output.push('\n');
+        }
+        output
+    }
+
+    /// Every frequency has its own colour, antinodes of several frequencies are white.
+    pub fn ansi(&self, frequencies: &Frequencies) -> String {
+        let mut all: Vec<char> = self.map.antennas.keys().copied().collect();
+        all.sort();
+        let colour = |frequency: char| {
+            let index = all.binary_search(&frequency).unwrap_or(0);
+            ANSI_COLOURS[index % ANSI_COLOURS.len()]
+        };
+
+        let mut output = String::new();
+        for row in self.cells(frequencies) {
+            for cell in row {
+                let (style, glyph) = match cell {
+                    Cell::Empty => (ANSI_DIM, '.'),
+                    Cell::Antenna(frequency) => (colour(frequency), frequency),
+                    Cell::Antinode(shown) if shown.len() == 1 => (colour(shown[0]), '#'),
+                    Cell::Antinode(_) => (ANSI_SHARED, '#'),
+                };
+                output.push_str(style);
+                output.push(glyph);
+                output.push_str(ANSI_RESET);
+            }
+            output.push('\n');
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::antinodes::Model;
+
+    const EXAMPLE: &str = "..........
+...#......
+..........
+....a.....
+..........
+.....a....
+..........
+......#...
+..........
+..........";
+
+    #[test]
+    fn test_ascii_overlay() {
+        let map = AntennasMap::parse(EXAMPLE.as_bytes()).unwrap();
+        let antinodes = map.antinodes(Model::Pairs);
+        let output = Renderer::new(&map, &antinodes).ascii(&Frequencies::All);
+        assert_eq!(output, format!("{EXAMPLE}\n"));
+
+        let hidden = Renderer::new(&map, &antinodes).ascii(&Frequencies::Only(['A'].into()));
+        assert_eq!(hidden, format!("{}\n", EXAMPLE.replace(['#', 'a'], ".")));
+    }
+
+    #[test]
+    fn test_ansi_overlay() {
+        let map = AntennasMap::parse("a.a..A".as_bytes()).unwrap();
+        let antinodes = map.antinodes(Model::Harmonics { reduce: false });
+        let output = Renderer::new(&map, &antinodes).ansi(&Frequencies::All);
+        assert_eq!(
+            output,
+            concat!(
+                "\x1b[1;32ma\x1b[0m\x1b[2m.\x1b[0m\x1b[1;32ma\x1b[0m",
+                "\x1b[2m.\x1b[0m\x1b[1;32m#\x1b[0m\x1b[1;31mA\x1b[0m\n"
+            )
+        );
+    }
+}
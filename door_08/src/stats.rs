@@ -0,0 +1,204 @@
+use crate::AntennasMap;
+use crate::antinodes::{Antinodes, Model};
+use std::collections::HashSet;
+
+/// Antinodes of one frequency under one model.
+#[derive(Debug, Default, PartialEq)]
+pub struct Counts {
+    pub antinodes: usize,
+    /// Antinodes that other frequencies create as well.
+    pub shared: usize,
+    /// Antinodes on the position of an antenna of any frequency.
+    pub on_antennas: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Statistics {
+    pub frequency: char,
+    pub antennas: usize,
+    pub part_one: Counts,
+    pub part_two: Counts,
+}
+
+impl<const D: usize> AntennasMap<D> {
+    /// One entry per frequency, ordered by frequency.
+    pub fn statistics(&self) -> Vec<Statistics> {
+        let antennas: HashSet<_> = self.antennas.values().flatten().collect();
+        let counts = |antinodes: &Antinodes<D>, frequency: char| {
+            let mut counts = Counts::default();
+            for (position, frequencies) in antinodes {
+                if !frequencies.contains(&frequency) {
+                    continue;
+                }
+                counts.antinodes += 1;
+                counts.shared += usize::from(frequencies.len() > 1);
+                counts.on_antennas += usize::from(antennas.contains(position));
+            }
+            counts
+        };
+
+        let part_one = self.antinodes(Model::Pairs);
+        let part_two = self.antinodes(Model::Harmonics { reduce: false });
+        let mut frequencies: Vec<char> = self.antennas.keys().copied().collect();
+        frequencies.sort();
+        frequencies
+            .into_iter()
+            .map(|frequency| Statistics {
+                frequency,
+                antennas: self.antennas[&frequency].len(),
+                part_one: counts(&part_one, frequency),
+                part_two: counts(&part_two, frequency),
+            })
+            .collect()
+    }
+}
+
+pub fn table(statistics: &[Statistics]) -> String {
+    let mut output = format!(
+        "{:<9} {:>8} | {:>9} {:>6} {:>10} | {:>9} {:>6} {:>10}\n",
+        "frequency",
+        "antennas",
+        "part one",
+        "shared",
+        "on antenna",
+        "part two",
+        "shared",
+        "on antenna"
+    );
+    for entry in statistics {
+        output.push_str(&format!(
+            "{:<9} {:>8} | {:>9} {:>6} {:>10} | {:>9} {:>6} {:>10}\n",
+            entry.frequency,
+            entry.antennas,
+            entry.part_one.antinodes,
+            entry.part_one.shared,
+            entry.part_one.on_antennas,
+            entry.part_two.antinodes,
+            entry.part_two.shared,
+            entry.part_two.on_antennas
+        ));
+    }
+    output
+}
+
+pub fn json(statistics: &[Statistics]) -> String {
+    let counts = |counts: &Counts| {
+        format!(
+            "{{\"antinodes\": {}, \"shared\": {}, \"on_antennas\": {}}}",
+            counts.antinodes, counts.shared, counts.on_antennas
+        )
+    };
+    let entries: Vec<String> = statistics
+        .iter()
+        .map(|entry| {
+            format!(
+                "  {{\"frequency\": \"{}\", \"antennas\": {}, \"part_one\": {}, \"part_two\": {}}}",
+                json_escape(entry.frequency),
+                entry.antennas,
+                counts(&entry.part_one),
+                counts(&entry.part_two)
+            )
+        })
+        .collect();
+    format!("[\n{}\n]\n", entries.join(",\n"))
+}
+
+fn json_escape(c: char) -> String {
+    match c {
+        '"' => String::from("\\\""),
+        '\\' => String::from("\\\\"),
+        c if (c as u32) < 0x20 => format!("\\u{:04x}", c as u32),
+        c => c.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "..........
+...a......
+..........
+....a.....
+..........
+.....A....
+..........
+.....A....
+..........
+..........";
+
+    #[test]
+    fn test_statistics() {
+        let map = AntennasMap::parse(EXAMPLE.as_bytes()).unwrap();
+        let statistics = map.statistics();
+        assert_eq!(statistics.len(), 2);
+
+        // the lines of both frequencies cross on an A antenna
+        assert_eq!(statistics[0].frequency, 'A');
+        assert_eq!(
+            statistics[0].part_two,
+            Counts {
+                antinodes: 5,
+                shared: 1,
+                on_antennas: 2
+            }
+        );
+        assert_eq!(
+            statistics[1],
+            Statistics {
+                frequency: 'a',
+                antennas: 2,
+                part_one: Counts {
+                    antinodes: 1,
+                    shared: 0,
+                    on_antennas: 1
+                },
+                part_two: Counts {
+                    antinodes: 5,
+                    shared: 1,
+                    on_antennas: 3
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn test_report_formats() {
+        let map = AntennasMap::parse(EXAMPLE.as_bytes()).unwrap();
+        let statistics = map.statistics();
+
+        let table = table(&statistics);
+        assert_eq!(table.lines().count(), 3);
+        assert!(
+            table
+                .lines()
+                .nth(2)
+                .unwrap()
+                .starts_with("a                2 |")
+        );
+
+        let json = json(&statistics);
+        assert!(json.starts_with("[\n  {\"frequency\": \"A\", \"antennas\": 2,"));
+        assert!(
+            json.contains("\"part_one\": {\"antinodes\": 1, \"shared\": 0, \"on_antennas\": 1}")
+        );
+        assert!(json.ends_with("}}\n]\n"));
+    }
+
+    #[test]
+    fn test_json_escapes_frequencies() {
+        // the parsers only accept alphanumeric antennas, but the report takes any statistics
+        let statistics: Vec<Statistics> = ['"', '\\']
+            .into_iter()
+            .map(|frequency| Statistics {
+                frequency,
+                antennas: 1,
+                part_one: Counts::default(),
+                part_two: Counts::default(),
+            })
+            .collect();
+        let json = json(&statistics);
+        assert!(json.contains("{\"frequency\": \"\\\"\", \"antennas\": 1,"));
+        assert!(json.contains("{\"frequency\": \"\\\\\", \"antennas\": 1,"));
+    }
+}
@@ -1,3 +1,6 @@
+mod spans;
+
+use spans::Spans;
 use std::collections::VecDeque;
 use std::str::FromStr;
 
@@ -15,6 +18,8 @@ fn main() {
         .decomprese()
         .checksum();
     println!("sum: {}", sum);
+    let sum = Spans::from(INPUT_STRING).defragment_two().checksum();
+    println!("sum: {}", sum);
 }
 
 #[derive(Clone)]
@@ -142,8 +147,13 @@ impl From<&str> for Disc {
                 .fold((VecDeque::new(), 0), |(mut acc, mut id), (i, c)| {
                     let size = usize::from_str(&c.to_string()).unwrap();
                     if i % 2 == 0 {
-                        acc.push_back(Block::File(File { id, size }));
+                        // an empty file takes no blocks, the gaps around it are one gap
+                        if size > 0 {
+                            acc.push_back(Block::File(File { id, size }));
+                        }
                         id += 1;
+                    } else if let Some(Block::Empty(gap)) = acc.back_mut() {
+                        *gap += size;
                     } else {
                         acc.push_back(Block::Empty(size));
                     }
@@ -199,6 +209,10 @@ fn compacting(input: &mut [Option<u128>]) {
 }
 
 fn compacting_2(input: &mut [Option<u128>]) {
+    // nothing to move on a disc of empty files
+    if input.iter().all(Option::is_none) {
+        return;
+    }
     let mut right = input.len() - 1;
     let mut right_end = input.len() - 1;
 
@@ -226,7 +240,7 @@ fn compacting_2(input: &mut [Option<u128>]) {
                 left += 1;
                 left_end += 1;
 
-                if left >= input.len() || left >= right_end {
+                if left >= input.len() || left > right_end {
                     right = right_end;
                     break 'inner;
                 }
@@ -235,7 +249,7 @@ fn compacting_2(input: &mut [Option<u128>]) {
             while input[left_end].is_none() {
                 left_end += 1;
 
-                if left_end >= input.len() || left_end >= right_end {
+                if left_end >= input.len() || left_end > right_end + 1 {
                     right = right_end;
                     break 'inner;
                 }
@@ -341,6 +355,29 @@ mod tests {
         assert_eq!(sum, 2858);
     }
 
+    #[test]
+    fn test_compacting_2_gap_next_to_file() {
+        // the gap ends right before the file that fits into it
+        for (disc_map, expected) in [("111", 1), ("12101", 4)] {
+            let mut disc = create_disc(disc_map);
+            compacting_2(disc.as_mut_slice());
+            assert_eq!(calc_sum(disc.as_slice()), expected, "{disc_map}");
+        }
+    }
+
+    #[test]
+    fn test_empty_files() {
+        // there is nothing to move on a disc without blocks
+        for disc_map in ["0", "00", "011"] {
+            let mut disc = create_disc(disc_map);
+            compacting_2(disc.as_mut_slice());
+            assert_eq!(calc_sum(disc.as_slice()), 0, "{disc_map}");
+        }
+        // the gaps around the empty file form one gap of three blocks, the last file fits
+        let disc = Disc::from("1102203").defragment_two();
+        assert_eq!(disc.decomprese().checksum(), 36);
+    }
+
     #[test]
     fn test() {
         let mut disc = Disc::from("2333133121414131402");
@@ -0,0 +1,128 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// The blocks `start..start + size` of a file.
+struct Span {
+    id: usize,
+    start: usize,
+    size: usize,
+}
+
+/// The disc as spans of files, with the gaps between them kept in one min-heap of start
+/// blocks per gap size, so the leftmost gap a file fits into is found without scanning.
+pub struct Spans {
+    files: Vec<Span>,
+    /// Gaps on both sides of an empty file are one gap, so sizes can exceed a single digit.
+    gaps: Vec<BinaryHeap<Reverse<usize>>>,
+}
+
+impl Spans {
+    /// Moves every file once, in order of decreasing id, into the leftmost gap before it
+    /// that is large enough.
+    pub fn defragment_two(mut self) -> Self {
+        for file in self.files.iter_mut().rev() {
+            let leftmost = (file.size..self.gaps.len())
+                .filter_map(|size| self.gaps[size].peek().map(|&Reverse(start)| (start, size)))
+                .filter(|&(start, _)| start < file.start)
+                .min();
+
+            if let Some((start, size)) = leftmost {
+                self.gaps[size].pop();
+                if size > file.size {
+                    self.gaps[size - file.size].push(Reverse(start + file.size));
+                }
+                // the blocks the file leaves lie behind every file still to move
+                file.start = start;
+            }
+        }
+
+        self
+    }
+
+    pub fn checksum(&self) -> usize {
+        self.files
+            .iter()
+            .map(|file| {
+                file.id * (file.start * file.size + file.size * file.size.saturating_sub(1) / 2)
+            })
+            .sum()
+    }
+}
+
+impl From<&str> for Spans {
+    fn from(s: &str) -> Self {
+        let mut files = Vec::new();
+        let mut gaps: Vec<(usize, usize)> = Vec::new();
+        let mut start = 0;
+
+        for (i, c) in s.chars().enumerate() {
+            let size = c.to_digit(10).unwrap() as usize;
+            if i % 2 == 0 {
+                files.push(Span {
+                    id: files.len(),
+                    start,
+                    size,
+                });
+            } else if size > 0 {
+                match gaps.last_mut() {
+                    Some((gap_start, gap_size)) if *gap_start + *gap_size == start => {
+                        *gap_size += size
+                    }
+                    _ => gaps.push((start, size)),
+                }
+            }
+            start += size;
+        }
+
+        let largest = gaps.iter().map(|&(_, size)| size).max().unwrap_or(0);
+        let mut heaps = vec![BinaryHeap::new(); largest + 1];
+        for (start, size) in gaps {
+            heaps[size].push(Reverse(start));
+        }
+
+        Self { files, gaps: heaps }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Disc, calc_sum, compacting_2, create_disc};
+    use common::Xorshift;
+
+    #[test]
+    fn test_example() {
+        let spans = Spans::from("2333133121414131402").defragment_two();
+        assert_eq!(spans.checksum(), 2858);
+    }
+
+    #[test]
+    fn test_empty_file() {
+        assert_eq!(Spans::from("011").checksum(), 1);
+        assert_eq!(Spans::from("011").defragment_two().checksum(), 0);
+        // the gaps around the empty file form one gap of three blocks
+        assert_eq!(Spans::from("1102203").defragment_two().checksum(), 36);
+    }
+
+    #[test]
+    fn test_matches_compacting() {
+        let mut xorshift = Xorshift::new(0x9e3779b9);
+        let mut random = move || xorshift.next_u32();
+
+        for _ in 0..300 {
+            let len = 1 + 2 * (random() % 40) as usize;
+            let disc_map: String = (0..len)
+                .map(|_| char::from_digit(random() % 10, 10).unwrap())
+                .collect();
+
+            let checksum = Spans::from(disc_map.as_str()).defragment_two().checksum();
+
+            let mut blocks = create_disc(&disc_map);
+            compacting_2(&mut blocks);
+            assert_eq!(checksum as u128, calc_sum(&blocks), "{disc_map}");
+
+            let disc = Disc::from(disc_map.as_str()).defragment_two();
+            assert_eq!(checksum, disc.decomprese().checksum(), "{disc_map}");
+        }
+    }
+}